@@ -12,7 +12,7 @@ use imxrt_rt as _;
 /// This is checked in an automated test.
 static mut DATA: u32 = 5;
 
-#[unsafe(link_section = ".xip")]
+#[imxrt_rt::flash]
 #[unsafe(no_mangle)]
 #[inline(never)]
 fn increment_data() {
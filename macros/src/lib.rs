@@ -0,0 +1,161 @@
+//! Attribute macros for `imxrt-rt`.
+//!
+//! Don't depend on this package. Instead, use the macros re-exported
+//! by `imxrt-rt`.
+//!
+//! Each macro places a function or static into an input section that's
+//! named like `.imxrt_rt.<memory>.<kind>.<item>`. The `imxrt-rt` linker
+//! script maps these input sections into the memory. Keep these names in
+//! sync with the linker script.
+//!
+//! The `user_pre_init` macro exports a function with the symbol name that
+//! the runtime's pre-init function calls.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{quote, quote_spanned};
+use syn::{
+    Item, ItemFn, ReturnType, StaticMutability, parse_macro_input, parse_quote, spanned::Spanned,
+};
+
+/// Place a function or static in instruction tightly coupled memory (ITCM).
+///
+/// The runtime copies the item into ITCM before `main()`.
+#[proc_macro_attribute]
+pub fn itcm(args: TokenStream, input: TokenStream) -> TokenStream {
+    place("itcm", args, input)
+}
+
+/// Place a function or static in data tightly coupled memory (DTCM).
+///
+/// The runtime copies the item into DTCM before `main()`.
+#[proc_macro_attribute]
+pub fn dtcm(args: TokenStream, input: TokenStream) -> TokenStream {
+    place("dtcm", args, input)
+}
+
+/// Place a function or static in on-chip RAM (OCRAM).
+///
+/// The runtime copies the item into OCRAM before `main()`.
+#[proc_macro_attribute]
+pub fn ocram(args: TokenStream, input: TokenStream) -> TokenStream {
+    place("ocram", args, input)
+}
+
+/// Place a function or immutable static in flash.
+///
+/// Functions execute in place. Flash can't be written, so a static in flash
+/// can't have interior mutability. A `static mut`, or a static whose type
+/// contains an `UnsafeCell`, like an atomic, fails to compile.
+#[proc_macro_attribute]
+pub fn flash(args: TokenStream, input: TokenStream) -> TokenStream {
+    place("flash", args, input)
+}
+
+/// Call a function before `.data` and `.bss` are initialized.
+///
+/// The function must have the signature `unsafe fn()`. There can only be one
+/// such function in a program.
+#[proc_macro_attribute]
+pub fn user_pre_init(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(
+            Span::call_site(),
+            "#[user_pre_init] doesn't take any arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let mut function = parse_macro_input!(input as ItemFn);
+    let sig = &function.sig;
+    if sig.unsafety.is_none()
+        || sig.asyncness.is_some()
+        || sig.abi.is_some()
+        || !sig.generics.params.is_empty()
+        || !sig.inputs.is_empty()
+        || sig.variadic.is_some()
+        || !matches!(sig.output, ReturnType::Default)
+    {
+        return syn::Error::new_spanned(
+            sig,
+            "#[user_pre_init] function must have signature `unsafe fn()`",
+        )
+        .to_compile_error()
+        .into();
+    }
+    function.sig.abi = Some(parse_quote!(extern "C"));
+    quote! {
+        #[unsafe(export_name = "__imxrt_rt_user_pre_init")]
+        #function
+    }
+    .into()
+}
+
+fn place(memory: &str, args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(
+            Span::call_site(),
+            format!("#[{memory}] doesn't take any arguments"),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    match parse_macro_input!(input as Item) {
+        Item::Fn(function) => {
+            let section = format!(".imxrt_rt.{memory}.text.{}", function.sig.ident);
+            // An inlined function executes from its caller's memory.
+            let inline = if function
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("inline"))
+            {
+                quote!()
+            } else {
+                quote!(#[inline(never)])
+            };
+            quote! {
+                #[unsafe(link_section = #section)]
+                #inline
+                #function
+            }
+        }
+        Item::Static(item) => {
+            let kind = match item.mutability {
+                StaticMutability::Mut(_) if memory == "flash" => {
+                    return syn::Error::new_spanned(
+                        &item.mutability,
+                        "#[flash] cannot place a static mut in flash",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+                StaticMutability::Mut(_) => "data",
+                _ => "rodata",
+            };
+            let section = format!(".imxrt_rt.{memory}.{kind}.{}", item.ident);
+            // A constant can't borrow a value that has interior mutability. Borrowing
+            // the initializer in a constant rejects statics that could be written.
+            let freeze = if memory == "flash" {
+                let (ty, expr) = (&item.ty, &item.expr);
+                quote_spanned! {expr.span()=>
+                    const _: &#ty = &#expr;
+                }
+            } else {
+                quote!()
+            };
+            quote! {
+                #[unsafe(link_section = #section)]
+                #item
+                #freeze
+            }
+        }
+        item => syn::Error::new_spanned(
+            item,
+            format!("#[{memory}] can only be applied to functions and statics"),
+        )
+        .to_compile_error(),
+    }
+    .into()
+}
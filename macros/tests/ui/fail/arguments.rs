@@ -0,0 +1,9 @@
+use imxrt_rt_macros::{itcm, user_pre_init};
+
+#[itcm(fast)]
+fn function() {}
+
+#[user_pre_init(early)]
+unsafe fn pre_init() {}
+
+fn main() {}
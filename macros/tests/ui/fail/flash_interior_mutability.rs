@@ -0,0 +1,17 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::AtomicU32;
+
+use imxrt_rt_macros::flash;
+
+#[flash]
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+struct Racy(UnsafeCell<u32>);
+unsafe impl Sync for Racy {}
+
+#[flash]
+static RACY: Racy = Racy(UnsafeCell::new(0));
+
+fn main() {
+    let _ = unsafe { *RACY.0.get() };
+}
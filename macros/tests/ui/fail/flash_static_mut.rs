@@ -0,0 +1,6 @@
+use imxrt_rt_macros::flash;
+
+#[flash]
+static mut BUFFER: [u8; 4] = [0; 4];
+
+fn main() {}
@@ -0,0 +1,6 @@
+use imxrt_rt_macros::dtcm;
+
+#[dtcm]
+struct Samples([i32; 4]);
+
+fn main() {}
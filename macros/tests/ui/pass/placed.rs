@@ -0,0 +1,37 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use imxrt_rt_macros::{dtcm, flash, itcm, ocram};
+
+#[itcm]
+fn itcm_function() -> u32 {
+    1
+}
+
+#[flash]
+#[inline]
+fn flash_function() -> u32 {
+    2
+}
+
+#[dtcm]
+static mut SAMPLES: [i32; 4] = [0; 4];
+
+#[ocram]
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+#[flash]
+static LOOKUP: [u16; 4] = [1, 2, 3, 4];
+
+#[flash]
+static NAME: &str = "imxrt-rt";
+
+#[flash]
+static LOOKUP_REF: &[u16; 4] = &LOOKUP;
+
+fn main() {
+    COUNTER.fetch_add(itcm_function() + flash_function(), Ordering::Relaxed);
+    let samples = unsafe { *core::ptr::addr_of!(SAMPLES) };
+    assert_eq!(samples, [0; 4]);
+    assert_eq!(LOOKUP_REF[3], 4);
+    assert_eq!(NAME.len(), 8);
+}
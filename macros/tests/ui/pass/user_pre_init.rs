@@ -0,0 +1,6 @@
+use imxrt_rt_macros::user_pre_init;
+
+#[user_pre_init]
+unsafe fn configure_sdram() {}
+
+fn main() {}
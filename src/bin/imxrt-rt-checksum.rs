@@ -0,0 +1,37 @@
+//! Add the image checksum to a program that's linked with imxrt-rt.
+//!
+//! ```text
+//! imxrt-rt-checksum <ELF> [<OUTPUT>]
+//! ```
+//!
+//! Without an output path, the checksum is added in place.
+
+use std::{env, fs, process::ExitCode};
+
+const USAGE: &str = "usage: imxrt-rt-checksum <ELF> [<OUTPUT>]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (input, output) = match args.as_slice() {
+        [help] if help == "-h" || help == "--help" => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        [input] => (input, input),
+        [input, output] => (input, output),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let checked = fs::read(input)
+        .map_err(Into::into)
+        .and_then(|contents| imxrt_rt::checksum_image(&contents))
+        .and_then(|checked| Ok(fs::write(output, checked)?));
+    if let Err(err) = checked {
+        eprintln!("{input}: {err}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,37 @@
+//! Compress the load images of a program that's linked with imxrt-rt.
+//!
+//! ```text
+//! imxrt-rt-compress <ELF> [<OUTPUT>]
+//! ```
+//!
+//! Without an output path, the program is compressed in place.
+
+use std::{env, fs, process::ExitCode};
+
+const USAGE: &str = "usage: imxrt-rt-compress <ELF> [<OUTPUT>]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (input, output) = match args.as_slice() {
+        [help] if help == "-h" || help == "--help" => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        [input] => (input, input),
+        [input, output] => (input, output),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let compressed = fs::read(input)
+        .map_err(Into::into)
+        .and_then(|contents| imxrt_rt::compress_load_images(&contents))
+        .and_then(|compressed| Ok(fs::write(output, compressed)?));
+    if let Err(err) = compressed {
+        eprintln!("{input}: {err}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
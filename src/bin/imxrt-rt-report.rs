@@ -0,0 +1,47 @@
+//! Print the memory usage of a program that's linked with imxrt-rt.
+//!
+//! ```text
+//! imxrt-rt-report [--json] <ELF>
+//! ```
+
+use std::{env, fs, process::ExitCode};
+
+use imxrt_rt::MemoryReport;
+
+const USAGE: &str = "usage: imxrt-rt-report [--json] <ELF>";
+
+fn main() -> ExitCode {
+    let mut json = false;
+    let mut path = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let Some(path) = path else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let report = fs::read(&path)
+        .map_err(Into::into)
+        .and_then(|contents| MemoryReport::from_elf(&contents));
+    match report {
+        Ok(report) if json => println!("{}", report.to_json()),
+        Ok(report) => print!("{report}"),
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
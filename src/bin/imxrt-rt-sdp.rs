@@ -0,0 +1,46 @@
+//! Export the serial download image of a program that's linked with imxrt-rt.
+//!
+//! ```text
+//! imxrt-rt-sdp <ELF> <OUTPUT>
+//! ```
+//!
+//! Writes the binary to the output path, then prints the load address and the
+//! jump address.
+
+use std::{env, fs, process::ExitCode};
+
+const USAGE: &str = "usage: imxrt-rt-sdp <ELF> <OUTPUT>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (input, output) = match args.as_slice() {
+        [help] if help == "-h" || help == "--help" => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        [input, output] => (input, output),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let image = fs::read(input)
+        .map_err(Into::into)
+        .and_then(|contents| imxrt_rt::serial_download_image(&contents))
+        .and_then(|image| {
+            fs::write(output, &image.binary)?;
+            Ok(image)
+        });
+    match image {
+        Ok(image) => {
+            println!("WRITE_FILE {:#010X}", image.load_address);
+            println!("JUMP_ADDRESS {:#010X}", image.jump_address);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{input}: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -16,6 +16,39 @@ use std::{
     path::PathBuf,
 };
 
+#[cfg(any(feature = "compress", feature = "checksum", feature = "sdp"))]
+mod elf;
+#[cfg(feature = "report")]
+mod report;
+#[cfg(feature = "report")]
+pub use report::{FlexRamUsage, MemoryReport, RegionUsage, SectionUsage};
+#[cfg(feature = "compress")]
+mod compress;
+#[cfg(feature = "compress")]
+pub use compress::compress_load_images;
+#[cfg(feature = "checksum")]
+mod checksum;
+#[cfg(feature = "checksum")]
+pub use checksum::checksum_image;
+#[cfg(feature = "sdp")]
+mod sdp;
+#[cfg(feature = "sdp")]
+pub use sdp::{SerialDownloadImage, serial_download_image};
+
+/// The origin of the memory region that stages compressed load images.
+///
+/// Nothing is loaded here. The region only exists until
+/// `compress_load_images` moves the compressed images into flash.
+const LZ4_STAGING_ORIGIN: u32 = 0xF000_0000;
+/// The size of the staging region.
+const LZ4_STAGING_LENGTH: u32 = 0x0800_0000;
+
+/// The size of the image check record, including its CRC table.
+///
+/// The record is the failure action, the start and end of the checked image,
+/// and the CRC. Keep the layout in sync with `__pre_init`.
+const IMAGE_CHECK_RECORD_SIZE: u32 = 16 + 4 * 256;
+
 /// Memory partitions.
 ///
 /// Use with [`RuntimeBuilder`] to specify the placement of sections
@@ -119,17 +152,91 @@ impl Display for Memory {
     }
 }
 
+/// Program sections that can be given a size budget.
+///
+/// Use with [`RuntimeBuilder::budget`] to limit the size of a section.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Code, or `.text`.
+    Text,
+    /// Read-only data, or `.rodata`.
+    Rodata,
+    /// Mutable data, or `.data`.
+    Data,
+    /// Zero-initialized data, or `.bss`.
+    Bss,
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Text => f.write_str(".text"),
+            Self::Rodata => f.write_str(".rodata"),
+            Self::Data => f.write_str(".data"),
+            Self::Bss => f.write_str(".bss"),
+        }
+    }
+}
+
+/// The action taken when the runtime's image check fails.
+///
+/// Use with [`RuntimeBuilder::image_check`]. See [image checks](RuntimeBuilder#image-checks)
+/// for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageCheckFailure {
+    /// Loop forever.
+    Spin,
+    /// Request a system reset.
+    Reset,
+    /// Call `ImageCheckFailed`, a function that you define in `.xip`.
+    Handler,
+}
+
+/// The action taken after the runtime's fault handler records a fault.
+///
+/// Use with [`RuntimeBuilder::fault_handler`]. See [fault handler](RuntimeBuilder#fault-handler)
+/// for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultAction {
+    /// Request a system reset.
+    Reset,
+    /// Call `FaultRecorded`, a function that you define.
+    Handler,
+}
+
 /// Define an alias for `name` that maps to a memory block named `placement`.
 fn region_alias(output: &mut dyn Write, name: &str, placement: Memory) -> io::Result<()> {
     writeln!(output, "REGION_ALIAS(\"REGION_{name}\", {placement});")
 }
 
+/// An additional heap, separate from the runtime's primary heap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NamedHeap {
+    name: String,
+    memory: Memory,
+    size: EnvOverride,
+}
+
+impl NamedHeap {
+    /// Returns `true` if the name can be used in a linker symbol and a Rust identifier.
+    fn has_valid_name(&self) -> bool {
+        let mut chars = self.name.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct FlashOpts {
     size: usize,
     offset: u32,
     flexspi: FlexSpi,
     boot_header: bool,
+    image_budget: Option<usize>,
+    rom_copy: Option<(Memory, usize)>,
 }
 
 impl FlashOpts {
@@ -156,6 +263,11 @@ impl EnvOverride {
         self.env = Some(key);
     }
     fn read(&self) -> Result<usize, Box<dyn std::error::Error>> {
+        Ok(self.read_env()?.unwrap_or(self.default))
+    }
+    /// Like [`read`](Self::read), but returns `None` if there's no value
+    /// from the environment.
+    fn read_env(&self) -> Result<Option<usize>, Box<dyn std::error::Error>> {
         if let Some(env) = &self.env {
             // If the user sets multiple environment variables for the same runtime
             // property (like stack, heap), we will only re-run when the variable
@@ -192,11 +304,45 @@ impl EnvOverride {
             } else {
                 val.parse::<usize>()?
             };
-            Ok(val)
+            Ok(Some(val))
         } else {
-            Ok(self.default)
+            Ok(None)
+        }
+    }
+}
+
+/// The size of a region, like the stack or heap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RegionSize {
+    /// The region has exactly this many bytes.
+    Fixed(usize),
+    /// The region consumes the remainder of its memory,
+    /// and it must have at least this many bytes.
+    Remainder(usize),
+}
+
+impl RegionSize {
+    /// Select the size from the environment, a remainder configuration,
+    /// or the default size, in that order.
+    fn new(
+        size: &EnvOverride,
+        remainder: Option<usize>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(match (size.read_env()?, remainder) {
+            (Some(bytes), _) => Self::Fixed(bytes),
+            (None, Some(minimum)) => Self::Remainder(minimum),
+            (None, None) => Self::Fixed(size.default),
+        })
+    }
+    /// The fixed size, or the minimum size of a remainder.
+    const fn bytes(self) -> usize {
+        match self {
+            Self::Fixed(bytes) | Self::Remainder(bytes) => bytes,
         }
     }
+    const fn is_remainder(self) -> bool {
+        matches!(self, Self::Remainder(_))
+    }
 }
 
 /// The MECC64 controller configuration.
@@ -256,7 +402,8 @@ impl FlexRamEcc {
 ///
 /// # Behaviors
 ///
-/// The implementation tries to place the stack in the lowest-possible memory addresses.
+/// Unless the stack [consumes the remainder of its region](Self#remainder-sizing),
+/// the implementation tries to place the stack in the lowest-possible memory addresses.
 /// This means the stack will grow down into reserved memory below DTCM and OCRAM for most
 /// chip families. The outlier is the 1170, where the stack will grow into OCRAM backdoor for
 /// the CM4 coprocessor. Be careful here...
@@ -379,235 +526,1219 @@ impl FlexRamEcc {
 /// Therefore, `YOUR_STACK_SIZE` controls the stack size, if set. Otherwise, the stack
 /// size is the default stack size.
 ///
-/// # ECC RAM
+/// # Remainder sizing
 ///
-/// Only some MCU families support ECC protection on RAM. There's two controllers supported
-/// by the runtime:
+/// Instead of guessing a fixed size, you can let the stack and / or heap consume
+/// all space that's left in its memory region after the other sections are placed.
+/// Use [`stack_size_remainder`](Self::stack_size_remainder) and
+/// [`heap_size_remainder`](Self::heap_size_remainder) to select this behavior.
+/// Each takes a minimum size; if the remaining space is smaller than the minimum,
+/// linking fails with an error that describes the problem.
 ///
-/// - MECC64, protecting dedicated OCRAM.
-/// - FlexRAM ECC, protecting TCM and FlexRAM-allocated OCRAM.
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .heap(Memory::Ocram)
+///     .heap_size_remainder(64 * 1024)
+///     .stack(Memory::Dtcm)
+///     .stack_size_remainder(8 * 1024)
+///     // ...
+///     # .build().unwrap();
+/// ```
 ///
-/// Use [`flexram_ecc`](Self::flexram_ecc) and [`mecc64`](Self::mecc64) to configure each
-/// controller. If your MCU family doesn't support the given controller, then the runtime
-/// builder will panic on your host.
+/// A heap that consumes the remainder of its region is still placed behind all
+/// other sections in that region. However, a stack that consumes the remainder of
+/// its region is placed behind all other sections in that region, instead of at
+/// the start of the region. As a consequence, a stack overflow will write into the
+/// sections below the stack.
 ///
-/// When you enable MECC64, it's enabled on both the OCRAM1 and OCRAM2 regions.
-/// Software does not provide an API to separate the configuration per OCRAM region.
+/// The stack and the heap cannot both consume the remainder of the same memory.
+/// Additionally, a heap that consumes the remainder of its memory cannot share that
+/// memory with a [named heap](Self::named_heap).
 ///
-/// When you enable FlexRAM ECC, the protection spans depend on your fuse configuration.
+/// If an environment override provides a size, the region uses that fixed size,
+/// even if you've selected remainder sizing.
 ///
-/// Typically, the runtime builder will treat dedicated OCRAM and FlexRAM-allocated OCRAM
-/// as a single, contiguous RAM region. However, if any ECC controller requires memory banks
-/// in between those two allocations, then the runtime allocates the OCRAM region into the
-/// *dedicated* OCRAM banks. To minimize the impact of this partition, minimize your FlexRAM
-/// OCRAM banks.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct RuntimeBuilder {
-    family: Family,
-    flexram_layout: Vec<FlexRamKind>,
-    text: Memory,
-    rodata: Memory,
-    data: Memory,
-    vectors: Memory,
-    bss: Memory,
-    uninit: Memory,
-    stack: Memory,
-    stack_size: EnvOverride,
-    heap: Memory,
-    heap_size: EnvOverride,
-    flash_opts: Option<FlashOpts>,
-    linker_script_name: String,
-    device_script_name: String,
-    flexram_ecc: FlexRamEcc,
-    mecc64: Mecc64,
-}
-
-const DEFAULT_LINKER_SCRIPT_NAME: &str = "imxrt-link.x";
-const DEFAULT_DEVICE_SCRIPT_NAME: &str = "device.x";
-
-impl RuntimeBuilder {
-    /// Creates a runtime that can execute and load contents from
-    /// FlexSPI flash.
-    ///
-    /// `flash_size` is the size of your flash component, in bytes.
-    pub fn from_flexspi(family: Family, flash_size: usize) -> Self {
-        Self {
-            family,
-            flexram_layout: family.default_flexram_layout(),
-            text: Memory::Itcm,
-            rodata: Memory::Ocram,
-            data: Memory::Ocram,
-            vectors: Memory::Dtcm,
-            bss: Memory::Ocram,
-            uninit: Memory::Ocram,
-            stack: Memory::Dtcm,
-            stack_size: EnvOverride::new(8 * 1024),
-            heap: Memory::Dtcm,
-            heap_size: EnvOverride::new(0),
-            flash_opts: Some(FlashOpts {
-                size: flash_size,
-                offset: 0,
-                boot_header: true,
-                flexspi: FlexSpi::family_default(family),
-            }),
-            linker_script_name: DEFAULT_LINKER_SCRIPT_NAME.into(),
-            device_script_name: DEFAULT_DEVICE_SCRIPT_NAME.into(),
-            flexram_ecc: FlexRamEcc::Disable,
-            mecc64: Mecc64::Disable,
-        }
-    }
-
-    /// Allocate a flash partition for this program to be booted by your software.
-    ///
-    /// `partition_size` is the size of the flash allocation, in bytes, for this
-    /// program. `partition_offset` describes the byte offset where the partition
-    /// starts. The offset is from the start of the FlexSPI memory region.
-    ///
-    /// The program constructed at this flash location cannot be booted by NXP's boot
-    /// ROM. You should bring your own software to execute this program. Note that
-    /// [the runtime behaviors](RuntimeBuilder) ensure that the vector table is placed
-    /// in flash at the given `partition_offset`.
-    ///
-    /// To compute a partition offset from two absolute flash addresses, use
-    /// [`Family::flexspi_start_addr`] to learn the FlexSPI starting address.
-    pub fn in_flash(family: Family, partition_size: usize, partition_offset: u32) -> Self {
-        Self {
-            family,
-            flexram_layout: family.default_flexram_layout(),
-            text: Memory::Itcm,
-            rodata: Memory::Ocram,
-            data: Memory::Ocram,
-            vectors: Memory::Dtcm,
-            bss: Memory::Ocram,
-            uninit: Memory::Ocram,
-            stack: Memory::Dtcm,
-            stack_size: EnvOverride::new(8 * 1024),
-            heap: Memory::Dtcm,
-            heap_size: EnvOverride::new(0),
-            flash_opts: Some(FlashOpts {
-                size: partition_size,
-                offset: partition_offset,
-                boot_header: false,
-                flexspi: FlexSpi::family_default(family),
-            }),
-            linker_script_name: DEFAULT_LINKER_SCRIPT_NAME.into(),
-            device_script_name: DEFAULT_DEVICE_SCRIPT_NAME.into(),
-            flexram_ecc: FlexRamEcc::Disable,
-            mecc64: Mecc64::Disable,
-        }
-    }
-
-    /// Create a runtime that executes from RAM.
-    pub fn from_ram(family: Family) -> Self {
-        Self {
-            family,
-            flexram_layout: family.default_flexram_layout(),
-            text: Memory::Itcm,
-            rodata: Memory::Ocram,
-            data: Memory::Ocram,
-            vectors: Memory::Dtcm,
-            bss: Memory::Ocram,
-            uninit: Memory::Ocram,
-            stack: Memory::Dtcm,
-            stack_size: EnvOverride::new(8 * 1024),
-            heap: Memory::Dtcm,
-            heap_size: EnvOverride::new(0),
-            flash_opts: None,
-            linker_script_name: DEFAULT_LINKER_SCRIPT_NAME.into(),
-            device_script_name: DEFAULT_DEVICE_SCRIPT_NAME.into(),
-            flexram_ecc: FlexRamEcc::Disable,
-            mecc64: Mecc64::Disable,
-        }
-    }
-
-    /// Set the FlexRAM bank allocation.
-    ///
-    /// Use this to customize the sizes of DTCM, ITCM, and OCRAM.
-    /// See the `FlexRamBanks` documentation for requirements on the
-    /// bank allocations.
-    pub fn flexram_banks(&mut self, flexram_banks: FlexRamBanks) -> &mut Self {
-        self.flexram_layout(&flexram_banks.to_flexram_layout())
-    }
-
-    /// Set the FlexRAM bank layout.
-    ///
-    /// Use this to customize the sizes of DTCM, ITCM, and OCRAM.
-    /// This also gives control of the bank assignment in the FlexRAM
-    /// controller.
-    pub fn flexram_layout(&mut self, flexram_layout: &[FlexRamKind]) -> &mut Self {
-        self.flexram_layout = Vec::from(flexram_layout);
-        self
-    }
-
-    /// Set the memory placement for code.
-    pub fn text(&mut self, memory: Memory) -> &mut Self {
-        self.text = memory;
-        self
-    }
-    /// Set the memory placement for read-only data.
-    pub fn rodata(&mut self, memory: Memory) -> &mut Self {
-        self.rodata = memory;
-        self
-    }
-    /// Set the memory placement for mutable data.
-    pub fn data(&mut self, memory: Memory) -> &mut Self {
-        self.data = memory;
-        self
-    }
-    /// Set the memory placement for the vector table.
-    pub fn vectors(&mut self, memory: Memory) -> &mut Self {
-        self.vectors = memory;
-        self
-    }
-    /// Set the memory placement for zero-initialized data.
-    pub fn bss(&mut self, memory: Memory) -> &mut Self {
-        self.bss = memory;
-        self
-    }
-    /// Set the memory placement for uninitialized data.
-    pub fn uninit(&mut self, memory: Memory) -> &mut Self {
-        self.uninit = memory;
-        self
-    }
-    /// Set the memory placement for stack memory.
-    pub fn stack(&mut self, memory: Memory) -> &mut Self {
-        self.stack = memory;
-        self
-    }
-    /// Set the size, in bytes, of the stack.
-    pub fn stack_size(&mut self, bytes: usize) -> &mut Self {
-        self.stack_size.default = bytes;
-        self
-    }
-    /// Let end users override the stack size using an environment variable.
-    ///
-    /// See the [environment overrides](Self#environment-overrides) documentation
-    /// for more information.
-    pub fn stack_size_env_override(&mut self, key: impl AsRef<str>) -> &mut Self {
-        self.stack_size.set_env_key(key.as_ref().into());
-        self
-    }
-    /// Set the memory placement for the heap.
-    ///
-    /// Note that the default heap has no size. Use [`heap_size`](Self::heap_size)
-    /// to allocate space for a heap.
-    pub fn heap(&mut self, memory: Memory) -> &mut Self {
-        self.heap = memory;
+/// # ITCM functions
+///
+/// When you place code in flash, all functions execute in place. To speed up
+/// frequently-called functions, like those reported by a profiler, use
+/// [`itcm_functions`](Self::itcm_functions) to copy them into ITCM. All other functions
+/// stay in flash.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .text(Memory::Flash)
+///     .itcm_functions(&["*my_driver*isr*", "*filter_block*", "memcpy"])
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The functions are placed into a `.itcm` section. The runtime copies this
+/// section into ITCM before `main()`. Patterns are matched against the names of the
+/// functions' input sections, so each function must be in its own section. Rust
+/// does this by default. Functions that are inlined into their callers aren't
+/// affected.
+///
+/// You need ITCM banks to use this feature.
+///
+/// # Crate code
+///
+/// Use [`crate_code`](Self::crate_code) to place all of a crate's code in a memory,
+/// no matter the placement of `.text`. For example, this configuration executes
+/// code from `micromath` and `dsp_filters` out of ITCM, while the rest of the program
+/// executes in place.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .text(Memory::Flash)
+///     .crate_code("micromath", Memory::Itcm)
+///     .crate_code("dsp-filters", Memory::Itcm)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// Each rule becomes an input section description that matches the object files
+/// of the crate's `rlib`. Code in ITCM, DTCM, and OCRAM is copied before `main()`,
+/// just like [ITCM functions](Self#itcm-functions). Code that's placed in flash
+/// executes in place, or it stays with `.text` in a RAM image.
+///
+/// A rule only captures code that the compiler generates in the crate's objects.
+/// Generic functions and inlined functions are generated in the crate that uses
+/// them, so they follow that crate's placement.
+///
+/// Link-time optimization (LTO) merges all crates into new objects before linking.
+/// When you enable LTO, the rules can't match any objects, and all code stays in
+/// its `.text` placement. Linking still succeeds. Consider the
+/// `#[imxrt_rt::itcm]` attribute, or [`itcm_functions`](Self::itcm_functions),
+/// for programs that use LTO.
+///
+/// # Text spill-over
+///
+/// When `.text` is placed in ITCM, a program that outgrows ITCM fails to link.
+/// Use [`text_spill`](Self::text_spill) to let `.text` spill into flash. `.text`
+/// fills up to a limit at the start of ITCM, and the code that doesn't fit executes
+/// in place from a `.text_spill` section in flash.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .text(Memory::Itcm)
+///     .text_spill(96 * 1024)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The linker decides what spills, and it considers input sections in the order
+/// they appear. To keep your hottest code in ITCM, place it with
+/// [`itcm_functions`](Self::itcm_functions), [`crate_code`](Self::crate_code),
+/// or `#[imxrt_rt::itcm]`. That code is placed in the rest of ITCM, behind the
+/// `.text` limit.
+///
+/// The linker only spills code if you link with `--enable-non-contiguous-regions`.
+/// Add this argument next to your linker script argument. Without the argument,
+/// linking fails once `.text` exceeds its limit.
+///
+/// ```text
+/// -C link-arg=-Timxrt-link.x -C link-arg=--enable-non-contiguous-regions
+/// ```
+///
+/// This mode requires a flash image with `.text` placed in ITCM.
+///
+/// # Compressed load images
+///
+/// When a section is placed in RAM, flash holds a copy of the section that the
+/// runtime copies before `main()`. Use [`compress`](Self::compress) to store that
+/// copy with LZ4 compression. The runtime decompresses the section, instead of copying
+/// it. You can compress `.text`, `.rodata`, and `.data`.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory, Section};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .text(Memory::Itcm)
+///     .compress(Section::Text)
+///     .compress(Section::Data)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// Compression happens after linking. The linker stages each compressed section in a
+/// memory region that doesn't exist, so linking doesn't need flash space for the
+/// uncompressed sections. After you link the program, compress it with
+/// `compress_load_images`, or with the `imxrt-rt-compress` program. Both are available
+/// with the `"compress"` feature.
+///
+/// ```text
+/// cargo install imxrt-rt --features compress
+/// imxrt-rt-compress path/to/firmware.elf
+/// ```
+///
+/// Compression places the compressed sections behind the rest of the flash image, and
+/// it fails if they don't fit in flash. The compressed program describes each compressed
+/// section with a section named like `.lz4.text`. **Don't flash a program that isn't
+/// compressed**; it won't start.
+///
+/// This mode requires a flash image. Compressed sections must be placed in RAM.
+///
+/// # Size budgets
+///
+/// A section that outgrows its memory fails to link. However, you might want linking
+/// to fail sooner, once a section exceeds the memory you've budgeted for your product.
+/// Use [`budget`](Self::budget) to limit the size of `.text`, `.rodata`, `.data`,
+/// and `.bss`. Use [`image_budget`](Self::image_budget) to limit the size of the
+/// flash image.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Section};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .budget(Section::Text, 96 * 1024)
+///     .budget(Section::Bss, 32 * 1024)
+///     .image_budget(256 * 1024)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The sizes include any padding that the linker inserts into the section.
+///
+/// # Boot timestamps
+///
+/// Use [`boot_timestamps`](Self::boot_timestamps) to measure how long the runtime
+/// takes to boot. The runtime starts the DWT cycle counter, then records the cycle
+/// count after each boot step. Read the counts with `imxrt_rt::boot_timestamps()`
+/// in your firmware.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .boot_timestamps(true)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The record occupies 36 bytes at the start of `.uninit`. Until the stack is usable,
+/// the runtime holds timestamps in FPU registers; it restores the FPU's configuration
+/// before copying any sections. The last timestamp follows the initialization of
+/// `.data` and `.bss`, so the runtime initializes them in its pre-init function, like
+/// it does for [static constructors](Self#static-constructors).
+///
+/// # Reset reasons
+///
+/// Use [`reset_reason`](Self::reset_reason) to learn why the chip last reset. The
+/// runtime captures the SRC reset status before it initializes memory, then it clears
+/// the status, so that the next capture only describes the next reset. Read the reason
+/// with `imxrt_rt::reset_reason()` in your firmware.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .reset_reason(true)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The status occupies 4 bytes in `.uninit`, behind any boot timestamps. Since the
+/// runtime clears the status, your firmware and HAL can't read it from SRC. Leave this
+/// disabled if they need to. The 1180 isn't supported yet.
+///
+/// # Image checks
+///
+/// Use [`image_check`](Self::image_check) to check the flash image before the runtime
+/// copies from it. The runtime computes a CRC-32 over the image, from the vector table to
+/// the end of the image, and it compares that CRC with the CRC that's stored in the image.
+/// If the CRCs differ, the runtime takes the [failure action](ImageCheckFailure).
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, ImageCheckFailure};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .image_check(ImageCheckFailure::Reset)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The CRC is stored after linking. Add it with `checksum_image`, or with the
+/// `imxrt-rt-checksum` program. Both are available with the `"checksum"` feature. If
+/// you [compress load images](Self#compressed-load-images), compress the program before
+/// adding the CRC. **Don't flash a program that doesn't have its CRC**; it fails the check.
+///
+/// ```text
+/// cargo install imxrt-rt --features checksum
+/// imxrt-rt-checksum path/to/firmware.elf
+/// ```
+///
+/// Gaps between the program's segments are expected to read as erased flash, `0xFF`.
+/// If you convert the program to a binary file, fill gaps with `0xFF`.
+///
+/// With [`ImageCheckFailure::Handler`], the runtime calls your `ImageCheckFailed`
+/// function. The handler runs before `.text` is copied and before statics are
+/// initialized, so it must be placed in `.xip`. Linking fails if it's placed elsewhere.
+///
+/// ```ignore
+/// #[unsafe(no_mangle)]
+/// #[unsafe(link_section = ".xip")]
+/// extern "C" fn ImageCheckFailed() -> ! {
+///     loop {}
+/// }
+/// ```
+///
+/// This mode requires a flash image.
+///
+/// # Static constructors
+///
+/// C and C++ libraries may have global constructors. Their compilers list the
+/// constructors in `.preinit_array` and `.init_array`. The runtime places these
+/// sections in `.rodata`, and it defines the usual `__preinit_array_start`,
+/// `__init_array_start`, and `__fini_array_start` symbols (along with their
+/// `_end` symbols). But by default, the runtime doesn't run the constructors.
+///
+/// Use [`init_arrays`](Self::init_arrays) to run the constructors before `main()`.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .init_arrays(true)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// Constructors expect initialized statics, so the runtime initializes `.data` and
+/// `.bss` in its pre-init function, instead of deferring to `cortex-m-rt`. It then
+/// enables the FPU, and it calls each function in `.preinit_array`, then each function
+/// in `.init_array`. The runtime calls the [user pre-init function](crate#running-code-before-main)
+/// before it runs any constructors.
+///
+/// Since `main()` never returns, the runtime never calls the functions in `.fini_array`.
+///
+/// # C libraries
+///
+/// C code that's built for newlib or picolibc expects its linker script to define
+/// symbols for the heap, stack, and sections. Use [`libc_symbols`](Self::libc_symbols)
+/// to define these symbols as aliases for the runtime's symbols.
+///
+/// | Symbols | Alias for |
+/// | --- | --- |
+/// | `end`, `_end`, `__end__`, `__HeapBase`, `__heap_start` | Start of the heap |
+/// | `__HeapLimit`, `__heap_end` | End of the heap |
+/// | `__StackTop`, `__stack` | Top of the stack, the initial stack pointer |
+/// | `__StackLimit` | Bottom of the stack |
+/// | `__data_start__`, `__data_end__` | Start and end of `.data` |
+/// | `__bss_start__`, `__bss_end__` | Start and end of `.bss` |
+///
+/// The runtime also provides `_sbrk`, which moves the program break through the
+/// runtime's heap. When the heap is exhausted, `_sbrk` returns `(void *)-1`, but
+/// it doesn't set `errno`. If your program defines any of these symbols, your
+/// definition is used instead.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .libc_symbols(true)
+///     .heap_size(16 * 1024)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The C library allocates from the same heap that your Rust allocator might use. If
+/// both languages allocate memory, give Rust a [named heap](Self::named_heap).
+///
+/// # Unwind tables
+///
+/// By default, the linker script discards the ARM exception index table, `.ARM.exidx`,
+/// and the exception table, `.ARM.extab`. Use [`unwind_tables`](Self::unwind_tables)
+/// to keep these tables for an on-target backtrace or unwinding library.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .unwind_tables(Memory::Ocram)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The tables are placed after `.rodata`. `__exidx_start` and `__exidx_end` mark the
+/// start and end of the index table. If the tables are placed in RAM, the runtime copies
+/// them there before `main()`. The compiler only emits complete tables if your program
+/// is built with unwind tables, for example with `-C force-unwind-tables=yes`.
+///
+/// Each entry in the index table holds a 31 bit offset to its function, so the table must
+/// be within 1 GiB of every function. No memory is a safe choice on every family. On the
+/// 1010 through 1060, ITCM and FlexSPI flash are 1.5 GiB apart, and DTCM is 1 GiB from
+/// flash. Tables in flash can't reach the default `.text` in ITCM, and tables in ITCM or
+/// DTCM can't reach code in flash. OCRAM is within reach of both. The builder compares
+/// the tables' memory with the memories of `.text`, spilled `.text`, ITCM functions, and
+/// crate code, and it rejects the tables if they can't reach one of these. It can't see
+/// functions placed with the attribute macros, like `#[imxrt_rt::flash]`. If the tables
+/// can't reach one of those functions, linking fails with an `R_ARM_PREL31` relocation
+/// error.
+///
+/// # Crash logs
+///
+/// Use [`crash_log`](Self::crash_log) to reserve RAM for a crash log. The runtime never
+/// copies into the crash log, and it only zeroes a log in ECC RAM after a power-on reset,
+/// so a record that's written before a reset can be read after the reset. Each record has a header with a magic number, the record's
+/// length, and the record's CRC-32. After a power-on reset, or after firmware changes the
+/// location of the crash log, the header is invalid, and there's no record to read.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .crash_log(Memory::Dtcm, 1024)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The crash log is placed in the `.crashlog` section, after the stack. The crash log
+/// is 8-byte aligned, and its size is rounded up to a multiple of 8 bytes. If the stack
+/// is in the same memory and has a fixed size, the stack grows down and away from the
+/// crash log, so a stack overflow can't reach it. A stack that uses
+/// [`stack_size_remainder`](Self::stack_size_remainder) is placed at the end of the
+/// region, and it grows down toward the crash log. A stack overflow could overwrite
+/// that crash log.
+///
+/// In your firmware, write a record with `CrashLogWriter`, then read it after reset
+/// with `read_crash_log()`. If you place the crash log in OCRAM, clean the data cache
+/// after writing a record, or the record could be lost on reset. DTCM isn't cached.
+///
+/// The runtime zeroes ECC-protected RAM on every boot, but it skips a crash log that's
+/// in that RAM. After a power-on reset, the crash log's ECC check bits are invalid, and
+/// reading the log would raise an uncorrectable error, so the runtime zeroes the log,
+/// too. It learns about the power-on reset from the SRC reset status, which only
+/// describes the most recent reset if the runtime clears it on every boot. To place the
+/// crash log in ECC RAM, enable [`reset_reason`](Self::reset_reason).
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, FlexRamEcc, RuntimeBuilder, Memory};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// RuntimeBuilder::from_flexspi(Family::Imxrt1170, FLASH_SIZE)
+///     .flexram_ecc(FlexRamEcc::Enable)
+///     .crash_log(Memory::Dtcm, 1024)
+///     .reset_reason(true)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// # Fault handler
+///
+/// Use [`fault_handler`](Self::fault_handler) to handle HardFault, MemoryManagement,
+/// BusFault, and UsageFault with the runtime's fault handler. The handler records the
+/// stacked exception frame, the active exception number, and the CFSR, HFSR, MMFAR, and
+/// BFAR registers in RAM. Then, it takes a [`FaultAction`].
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory, FaultAction};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .fault_handler(Memory::Dtcm, FaultAction::Reset)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The record is never copied into or zeroed, so it survives a reset.
+/// It's placed in the `.fault_record` section, after the crash log. Read the record
+/// with `fault_record()`, and clear it with `clear_fault_record()`. Unlike the crash log,
+/// you can't place the record in a memory that's protected by ECC.
+///
+/// With [`FaultAction::Handler`], the runtime calls your `FaultRecorded` function after
+/// it records the fault. Define the function like
+///
+/// ```ignore
+/// #[unsafe(no_mangle)]
+/// extern "C" fn FaultRecorded(record: &imxrt_rt::FaultRecord) -> ! {
+///     // ...
+/// }
+/// ```
+///
+/// The handler is only used for exceptions that you don't handle yourself. If you
+/// define a `HardFault` handler with `#[exception]`, your handler is used instead.
+/// MemoryManagement, BusFault, and UsageFault escalate to HardFault unless you
+/// enable them.
+///
+/// # Position-independent images
+///
+/// Use [`position_independent`](Self::position_independent) to build a program that runs
+/// from any flash partition, not just the partition given to [`in_flash`](Self::in_flash).
+/// Write the same program to whichever A/B slot is free.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::in_flash(family, 1024 * 1024, 0x10_0000)
+///     .position_independent(true)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The program's code and data run from RAM at fixed addresses, so you don't need to
+/// compile position-independent code. Only the load images in flash move. The runtime
+/// finds its load offset, the distance from the linked partition to the partition that
+/// it runs from, then copies every section from its actual load address using the copy
+/// table. `.data` is initialized by this copy. The global offset table, if there is one,
+/// is copied into RAM with `.data`, and entries that point into flash move by the load
+/// offset.
+///
+/// The program can't have anything that runs or is read from flash at its linked
+/// address. The vector table, `.text`, `.rodata`, `.data`, and unwind tables can't be
+/// placed in flash, and you can't place items in flash with `#[imxrt_rt::flash]` or
+/// [`crate_code`](Self::crate_code). Linking fails if you place items in flash. The
+/// program can't spill `.text` into flash, or check its image.
+///
+/// The vector table in flash still holds linked addresses. To boot the program from
+/// a bootloader, use `BootImage::new_position_independent`.
+///
+/// # Boot ROM copies
+///
+/// By default, the boot ROM executes the image in place, and the runtime copies sections
+/// out of flash. Use [`rom_copy`](Self::rom_copy) to have the boot ROM copy the whole
+/// image, from the start of flash through the load image of `.data`, into ITCM or OCRAM.
+/// The boot ROM then starts the program from its copy, and nothing executes from flash.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # const FLASH_SIZE: usize = 16 * 1024;
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+///     .rom_copy(Memory::Itcm, 64 * 1024)
+///     .text(Memory::Flash)
+///     .rodata(Memory::Flash)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The copy takes the given number of bytes from the start of its memory. The image
+/// is linked to run from the copy, so `Memory::Flash` describes the copy, and sections
+/// placed in flash are never copied again. The runtime still copies sections that are
+/// placed in other memories, but it copies them from RAM. The boot data and image vector
+/// table tell the boot ROM where to copy the image, and how large it is.
+///
+/// The program's load addresses are in RAM. To program flash, convert the program to a
+/// binary, and write that binary to the start of flash. A debugger can load the program
+/// into RAM, and run it like a [RAM image](Self::from_ram).
+///
+/// ```text
+/// rust-objcopy -O binary path/to/firmware.elf firmware.bin
+/// ```
+///
+/// The boot ROM copies the image before the runtime configures FlexRAM, and before the
+/// runtime zeroes ECC RAM. The image must use the default FlexRAM layout, and you can't
+/// copy it into memory that's protected by ECC. The boot ROM uses some OCRAM while it
+/// boots; consult your reference manual before you copy the image into OCRAM.
+///
+/// This mode requires a flash image with a boot header. It's not available for the
+/// 1180. The image can't spill `.text`, compress its load images, or check its image.
+///
+/// # Serial download images
+///
+/// A [RAM image](Self::from_ram) has no boot header, so it can only be loaded by a
+/// debugger. Use [`serial_download`](Self::serial_download) to build a RAM image that
+/// the boot ROM's serial downloader (SDP) can load over USB or UART.
+///
+/// ```no_run
+/// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+/// # let family = Family::Imxrt1060;
+/// RuntimeBuilder::from_ram(family)
+///     .serial_download(Memory::Ocram, 128 * 1024)
+///     // ...
+///     # .build().unwrap();
+/// ```
+///
+/// The image takes the given number of bytes from the start of its memory. It starts
+/// with an image vector table and boot data, 1 KiB from the start of the image, and
+/// it holds the load images of all sections. `Memory::Flash` describes the image, so
+/// sections placed in flash execute in place from RAM. The runtime copies sections
+/// that are placed in other memories out of the image.
+///
+/// To load the image, write its binary to the load address with `WRITE_FILE`, then
+/// `JUMP_ADDRESS` to its image vector table. With the `"sdp"` feature, use
+/// `imxrt_rt::serial_download_image`, or the `imxrt-rt-sdp` binary, to export the
+/// binary and these addresses from the program. A debugger can still load the image.
+///
+/// ```text
+/// imxrt-rt-sdp path/to/firmware.elf firmware.bin
+/// ```
+///
+/// Like [boot ROM copies](Self#boot-rom-copies), the image is loaded before the runtime
+/// configures FlexRAM and zeroes ECC RAM. It must use the default FlexRAM layout, it
+/// can't be loaded into memory that's protected by ECC, and it's not available for the
+/// 1180. The serial downloader uses some OCRAM; consult your reference manual before you
+/// load the image into OCRAM.
+///
+/// # ECC RAM
+///
+/// Only some MCU families support ECC protection on RAM. There's two controllers supported
+/// by the runtime:
+///
+/// - MECC64, protecting dedicated OCRAM.
+/// - FlexRAM ECC, protecting TCM and FlexRAM-allocated OCRAM.
+///
+/// Use [`flexram_ecc`](Self::flexram_ecc) and [`mecc64`](Self::mecc64) to configure each
+/// controller. If your MCU family doesn't support the given controller, then the runtime
+/// builder will panic on your host.
+///
+/// When you enable MECC64, it's enabled on both the OCRAM1 and OCRAM2 regions.
+/// Software does not provide an API to separate the configuration per OCRAM region.
+///
+/// When you enable FlexRAM ECC, the protection spans depend on your fuse configuration.
+///
+/// Typically, the runtime builder will treat dedicated OCRAM and FlexRAM-allocated OCRAM
+/// as a single, contiguous RAM region. However, if any ECC controller requires memory banks
+/// in between those two allocations, then the runtime allocates the OCRAM region into the
+/// *dedicated* OCRAM banks. To minimize the impact of this partition, minimize your FlexRAM
+/// OCRAM banks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeBuilder {
+    family: Family,
+    flexram_layout: Vec<FlexRamKind>,
+    flexram_fit: Option<RamRequirements>,
+    text: Memory,
+    rodata: Memory,
+    data: Memory,
+    vectors: Memory,
+    bss: Memory,
+    uninit: Memory,
+    stack: Memory,
+    stack_size: EnvOverride,
+    stack_remainder: Option<usize>,
+    heap: Memory,
+    heap_size: EnvOverride,
+    heap_remainder: Option<usize>,
+    named_heaps: Vec<NamedHeap>,
+    itcm_functions: Vec<String>,
+    crate_code: Vec<(String, Memory)>,
+    text_spill: Option<usize>,
+    compressed: Vec<Section>,
+    budgets: Vec<(Section, usize)>,
+    boot_timestamps: bool,
+    reset_reason: bool,
+    init_arrays: bool,
+    libc_symbols: bool,
+    unwind_tables: Option<Memory>,
+    crash_log: Option<(Memory, usize)>,
+    fault_handler: Option<(Memory, FaultAction)>,
+    position_independent: bool,
+    serial_download: Option<(Memory, usize)>,
+    image_check: Option<ImageCheckFailure>,
+    flash_opts: Option<FlashOpts>,
+    linker_script_name: String,
+    device_script_name: String,
+    flexram_ecc: FlexRamEcc,
+    mecc64: Mecc64,
+}
+
+const DEFAULT_LINKER_SCRIPT_NAME: &str = "imxrt-link.x";
+const DEFAULT_DEVICE_SCRIPT_NAME: &str = "device.x";
+
+/// The size of a crash log record's header.
+///
+/// The header is the magic number, the record length, and the record's
+/// CRC-32. Keep this in sync with the target's crash log.
+const CRASH_LOG_HEADER_SIZE: usize = 12;
+
+/// The size of the fault handler's record.
+///
+/// The record is a magic number, followed by the target's fault record.
+/// Keep this in sync with the target's fault handler.
+const FAULT_RECORD_SIZE: usize = 56;
+
+/// Output sections for items that are relocated into RAM by the copy table.
+///
+/// Each input section is named like the output section, prefixed with
+/// `.imxrt_rt`. Keep these in sync with the attribute macros.
+const RELOCATED_SECTIONS: [(Memory, &str); 3] = [
+    (Memory::Itcm, ".itcm"),
+    (Memory::Dtcm, ".dtcm"),
+    (Memory::Ocram, ".ocram"),
+];
+
+/// Output sections that keep the ARM unwind tables.
+const UNWIND_SECTIONS: [&str; 2] = [".ARM.extab", ".ARM.exidx"];
+
+/// Symbols that C libraries expect from their linker scripts, and the
+/// runtime symbols they alias.
+const LIBC_SYMBOLS: [(&str, &str); 14] = [
+    ("end", "__sheap"),
+    ("_end", "__sheap"),
+    ("__end__", "__sheap"),
+    ("__HeapBase", "__sheap"),
+    ("__HeapLimit", "__eheap"),
+    ("__heap_start", "__sheap"),
+    ("__heap_end", "__eheap"),
+    ("__StackTop", "__sstack"),
+    ("__StackLimit", "__estack"),
+    ("__stack", "__sstack"),
+    ("__data_start__", "__sdata"),
+    ("__data_end__", "__edata"),
+    ("__bss_start__", "__imxrt_rt_sbss"),
+    ("__bss_end__", "__ebss"),
+];
+
+impl RuntimeBuilder {
+    /// Creates a runtime that can execute and load contents from
+    /// FlexSPI flash.
+    ///
+    /// `flash_size` is the size of your flash component, in bytes.
+    pub fn from_flexspi(family: Family, flash_size: usize) -> Self {
+        Self {
+            family,
+            flexram_layout: family.default_flexram_layout(),
+            flexram_fit: None,
+            text: Memory::Itcm,
+            rodata: Memory::Ocram,
+            data: Memory::Ocram,
+            vectors: Memory::Dtcm,
+            bss: Memory::Ocram,
+            uninit: Memory::Ocram,
+            stack: Memory::Dtcm,
+            stack_size: EnvOverride::new(8 * 1024),
+            stack_remainder: None,
+            heap: Memory::Dtcm,
+            heap_size: EnvOverride::new(0),
+            heap_remainder: None,
+            named_heaps: Vec::new(),
+            itcm_functions: Vec::new(),
+            crate_code: Vec::new(),
+            text_spill: None,
+            compressed: Vec::new(),
+            budgets: Vec::new(),
+            boot_timestamps: false,
+            reset_reason: false,
+            init_arrays: false,
+            libc_symbols: false,
+            unwind_tables: None,
+            crash_log: None,
+            fault_handler: None,
+            position_independent: false,
+            serial_download: None,
+            image_check: None,
+            flash_opts: Some(FlashOpts {
+                size: flash_size,
+                offset: 0,
+                boot_header: true,
+                flexspi: FlexSpi::family_default(family),
+                image_budget: None,
+                rom_copy: None,
+            }),
+            linker_script_name: DEFAULT_LINKER_SCRIPT_NAME.into(),
+            device_script_name: DEFAULT_DEVICE_SCRIPT_NAME.into(),
+            flexram_ecc: FlexRamEcc::Disable,
+            mecc64: Mecc64::Disable,
+        }
+    }
+
+    /// Allocate a flash partition for this program to be booted by your software.
+    ///
+    /// `partition_size` is the size of the flash allocation, in bytes, for this
+    /// program. `partition_offset` describes the byte offset where the partition
+    /// starts. The offset is from the start of the FlexSPI memory region.
+    ///
+    /// The program constructed at this flash location cannot be booted by NXP's boot
+    /// ROM. You should bring your own software to execute this program. Note that
+    /// [the runtime behaviors](RuntimeBuilder) ensure that the vector table is placed
+    /// in flash at the given `partition_offset`. To boot this program from your
+    /// software, use `imxrt_rt::BootImage`.
+    ///
+    /// To compute a partition offset from two absolute flash addresses, use
+    /// [`Family::flexspi_start_addr`] to learn the FlexSPI starting address.
+    pub fn in_flash(family: Family, partition_size: usize, partition_offset: u32) -> Self {
+        Self {
+            family,
+            flexram_layout: family.default_flexram_layout(),
+            flexram_fit: None,
+            text: Memory::Itcm,
+            rodata: Memory::Ocram,
+            data: Memory::Ocram,
+            vectors: Memory::Dtcm,
+            bss: Memory::Ocram,
+            uninit: Memory::Ocram,
+            stack: Memory::Dtcm,
+            stack_size: EnvOverride::new(8 * 1024),
+            stack_remainder: None,
+            heap: Memory::Dtcm,
+            heap_size: EnvOverride::new(0),
+            heap_remainder: None,
+            named_heaps: Vec::new(),
+            itcm_functions: Vec::new(),
+            crate_code: Vec::new(),
+            text_spill: None,
+            compressed: Vec::new(),
+            budgets: Vec::new(),
+            boot_timestamps: false,
+            reset_reason: false,
+            init_arrays: false,
+            libc_symbols: false,
+            unwind_tables: None,
+            crash_log: None,
+            fault_handler: None,
+            position_independent: false,
+            serial_download: None,
+            image_check: None,
+            flash_opts: Some(FlashOpts {
+                size: partition_size,
+                offset: partition_offset,
+                boot_header: false,
+                flexspi: FlexSpi::family_default(family),
+                image_budget: None,
+                rom_copy: None,
+            }),
+            linker_script_name: DEFAULT_LINKER_SCRIPT_NAME.into(),
+            device_script_name: DEFAULT_DEVICE_SCRIPT_NAME.into(),
+            flexram_ecc: FlexRamEcc::Disable,
+            mecc64: Mecc64::Disable,
+        }
+    }
+
+    /// Create a runtime that executes from RAM.
+    pub fn from_ram(family: Family) -> Self {
+        Self {
+            family,
+            flexram_layout: family.default_flexram_layout(),
+            flexram_fit: None,
+            text: Memory::Itcm,
+            rodata: Memory::Ocram,
+            data: Memory::Ocram,
+            vectors: Memory::Dtcm,
+            bss: Memory::Ocram,
+            uninit: Memory::Ocram,
+            stack: Memory::Dtcm,
+            stack_size: EnvOverride::new(8 * 1024),
+            stack_remainder: None,
+            heap: Memory::Dtcm,
+            heap_size: EnvOverride::new(0),
+            heap_remainder: None,
+            named_heaps: Vec::new(),
+            itcm_functions: Vec::new(),
+            crate_code: Vec::new(),
+            text_spill: None,
+            compressed: Vec::new(),
+            budgets: Vec::new(),
+            boot_timestamps: false,
+            reset_reason: false,
+            init_arrays: false,
+            libc_symbols: false,
+            unwind_tables: None,
+            crash_log: None,
+            fault_handler: None,
+            position_independent: false,
+            serial_download: None,
+            image_check: None,
+            flash_opts: None,
+            linker_script_name: DEFAULT_LINKER_SCRIPT_NAME.into(),
+            device_script_name: DEFAULT_DEVICE_SCRIPT_NAME.into(),
+            flexram_ecc: FlexRamEcc::Disable,
+            mecc64: Mecc64::Disable,
+        }
+    }
+
+    /// Set the FlexRAM bank allocation.
+    ///
+    /// Use this to customize the sizes of DTCM, ITCM, and OCRAM.
+    /// See the `FlexRamBanks` documentation for requirements on the
+    /// bank allocations.
+    pub fn flexram_banks(&mut self, flexram_banks: FlexRamBanks) -> &mut Self {
+        self.flexram_layout(&flexram_banks.to_flexram_layout())
+    }
+
+    /// Set the FlexRAM bank layout.
+    ///
+    /// Use this to customize the sizes of DTCM, ITCM, and OCRAM.
+    /// This also gives control of the bank assignment in the FlexRAM
+    /// controller.
+    pub fn flexram_layout(&mut self, flexram_layout: &[FlexRamKind]) -> &mut Self {
+        self.flexram_layout = Vec::from(flexram_layout);
+        self.flexram_fit = None;
+        self
+    }
+
+    /// Allocate the fewest FlexRAM banks that satisfy your RAM requirements.
+    ///
+    /// The builder selects the banks with [`Family::fit_flexram_banks`] when
+    /// you invoke [`build()`](Self::build), so the selection considers your
+    /// ECC configuration. If there's no valid allocation, `build()` returns
+    /// an error.
+    ///
+    /// This overrides any previous call to [`flexram_banks`](Self::flexram_banks)
+    /// or [`flexram_layout`](Self::flexram_layout). Likewise, those calls override
+    /// this call.
+    pub fn fit_flexram_banks(&mut self, requirements: RamRequirements) -> &mut Self {
+        self.flexram_fit = Some(requirements);
+        self
+    }
+
+    /// Set the memory placement for code.
+    pub fn text(&mut self, memory: Memory) -> &mut Self {
+        self.text = memory;
+        self
+    }
+    /// Set the memory placement for read-only data.
+    pub fn rodata(&mut self, memory: Memory) -> &mut Self {
+        self.rodata = memory;
+        self
+    }
+    /// Set the memory placement for mutable data.
+    pub fn data(&mut self, memory: Memory) -> &mut Self {
+        self.data = memory;
+        self
+    }
+    /// Set the memory placement for the vector table.
+    pub fn vectors(&mut self, memory: Memory) -> &mut Self {
+        self.vectors = memory;
+        self
+    }
+    /// Set the memory placement for zero-initialized data.
+    pub fn bss(&mut self, memory: Memory) -> &mut Self {
+        self.bss = memory;
+        self
+    }
+    /// Set the memory placement for uninitialized data.
+    pub fn uninit(&mut self, memory: Memory) -> &mut Self {
+        self.uninit = memory;
+        self
+    }
+    /// Set the memory placement for stack memory.
+    pub fn stack(&mut self, memory: Memory) -> &mut Self {
+        self.stack = memory;
+        self
+    }
+    /// Set the size, in bytes, of the stack.
+    ///
+    /// This overrides any previous call to [`stack_size_remainder`](Self::stack_size_remainder).
+    pub fn stack_size(&mut self, bytes: usize) -> &mut Self {
+        self.stack_size.default = bytes;
+        self.stack_remainder = None;
+        self
+    }
+    /// Let the stack consume the remainder of its memory region.
+    ///
+    /// The stack takes all space that's left after placing the other sections
+    /// in its memory. If the stack would be smaller than `minimum` bytes, linking
+    /// fails. See [remainder sizing](Self#remainder-sizing) for more information.
+    ///
+    /// This overrides any previous call to [`stack_size`](Self::stack_size).
+    pub fn stack_size_remainder(&mut self, minimum: usize) -> &mut Self {
+        self.stack_remainder = Some(minimum);
+        self
+    }
+    /// Let end users override the stack size using an environment variable.
+    ///
+    /// See the [environment overrides](Self#environment-overrides) documentation
+    /// for more information.
+    pub fn stack_size_env_override(&mut self, key: impl AsRef<str>) -> &mut Self {
+        self.stack_size.set_env_key(key.as_ref().into());
+        self
+    }
+    /// Set the memory placement for the heap.
+    ///
+    /// Note that the default heap has no size. Use [`heap_size`](Self::heap_size)
+    /// to allocate space for a heap.
+    pub fn heap(&mut self, memory: Memory) -> &mut Self {
+        self.heap = memory;
+        self
+    }
+    /// Set the size, in bytes, of the heap.
+    ///
+    /// This overrides any previous call to [`heap_size_remainder`](Self::heap_size_remainder).
+    pub fn heap_size(&mut self, bytes: usize) -> &mut Self {
+        self.heap_size.default = bytes;
+        self.heap_remainder = None;
+        self
+    }
+    /// Let the heap consume the remainder of its memory region.
+    ///
+    /// The heap takes all space that's left after placing the other sections
+    /// in its memory. If the heap would be smaller than `minimum` bytes, linking
+    /// fails. See [remainder sizing](Self#remainder-sizing) for more information.
+    ///
+    /// This overrides any previous call to [`heap_size`](Self::heap_size).
+    pub fn heap_size_remainder(&mut self, minimum: usize) -> &mut Self {
+        self.heap_remainder = Some(minimum);
+        self
+    }
+    /// Let end users override the heap size using an environment variable.
+    ///
+    /// See the [environment overrides](Self#environment-overrides) documentation
+    /// for more information.
+    pub fn heap_size_env_override(&mut self, key: impl AsRef<str>) -> &mut Self {
+        self.heap_size.set_env_key(key.as_ref().into());
+        self
+    }
+    /// Allocate an additional heap named `name`.
+    ///
+    /// The heap is placed in `memory` and has a size of `bytes`. Use this
+    /// when you need more than one heap, possibly across different memories.
+    /// For example, you might want a small, fast DTCM heap for scratch buffers
+    /// and a large OCRAM heap for everything else.
+    ///
+    /// The name must be a valid Rust identifier that only uses ASCII characters.
+    /// The runtime exports the heap's boundaries as `__sheap_{name}` and
+    /// `__eheap_{name}`. In your firmware, use the `named_heap!` macro to
+    /// acquire these boundaries.
+    ///
+    /// Calling this again with the same name replaces that heap's memory and
+    /// size. Named heaps are independent of the primary heap configured with
+    /// [`heap`](Self::heap) and [`heap_size`](Self::heap_size).
+    ///
+    /// ```no_run
+    /// # use imxrt_rt::{Family, RuntimeBuilder, Memory};
+    /// # const FLASH_SIZE: usize = 16 * 1024;
+    /// # let family = Family::Imxrt1060;
+    /// RuntimeBuilder::from_flexspi(family, FLASH_SIZE)
+    ///     .named_heap("dsp", Memory::Dtcm, 16 * 1024)
+    ///     .named_heap("net", Memory::Ocram, 128 * 1024)
+    ///     .named_heap_size_env_override("net", "NET_HEAP_SIZE")
+    ///     // ...
+    ///     # .build().unwrap();
+    /// ```
+    pub fn named_heap(&mut self, name: &str, memory: Memory, bytes: usize) -> &mut Self {
+        if let Some(heap) = self.named_heaps.iter_mut().find(|heap| heap.name == name) {
+            heap.memory = memory;
+            heap.size.default = bytes;
+        } else {
+            self.named_heaps.push(NamedHeap {
+                name: name.into(),
+                memory,
+                size: EnvOverride::new(bytes),
+            });
+        }
+        self
+    }
+    /// Let end users override the size of the named heap using an environment variable.
+    ///
+    /// See the [environment overrides](Self#environment-overrides) documentation
+    /// for more information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no heap called `name`. Allocate the heap with
+    /// [`named_heap`](Self::named_heap) before calling this method.
+    pub fn named_heap_size_env_override(&mut self, name: &str, key: impl AsRef<str>) -> &mut Self {
+        let heap = self
+            .named_heaps
+            .iter_mut()
+            .find(|heap| heap.name == name)
+            .unwrap_or_else(|| panic!("There is no heap named '{name}'"));
+        heap.size.set_env_key(key.as_ref().into());
+        self
+    }
+    /// Place functions in ITCM, even if the rest of the code is elsewhere.
+    ///
+    /// Each pattern is matched against a function's symbol name. A pattern may
+    /// use `*` and `?` wildcards, just like an input section description in a linker
+    /// script. Since Rust mangles most symbol names, you'll typically surround the
+    /// name with wildcards. See [ITCM functions](Self#itcm-functions) for more
+    /// information.
+    ///
+    /// Call this multiple times to add more patterns.
+    pub fn itcm_functions(&mut self, patterns: &[&str]) -> &mut Self {
+        self.itcm_functions
+            .extend(patterns.iter().map(|pattern| String::from(*pattern)));
+        self
+    }
+    /// Place all code from the crate `crate_name` in `memory`.
+    ///
+    /// Use the crate's name as it appears in `Cargo.toml`. See [crate code](Self#crate-code)
+    /// for more information, including how this interacts with LTO.
+    ///
+    /// This overrides any previous placement for the same crate.
+    pub fn crate_code(&mut self, crate_name: &str, memory: Memory) -> &mut Self {
+        // Cargo replaces dashes when it names the crate's rlib.
+        let crate_name = crate_name.replace('-', "_");
+        self.crate_code.retain(|(name, _)| *name != crate_name);
+        self.crate_code.push((crate_name, memory));
+        self
+    }
+    /// Let `.text` spill from ITCM into flash.
+    ///
+    /// `.text` fills up to `itcm_limit` bytes at the start of ITCM. The remaining
+    /// code executes in place from flash. See [text spill-over](Self#text-spill-over)
+    /// for more information, including the linker flag that this requires.
+    pub fn text_spill(&mut self, itcm_limit: usize) -> &mut Self {
+        self.text_spill = Some(itcm_limit);
+        self
+    }
+    /// Store the load image of `section` with LZ4 compression.
+    ///
+    /// The runtime decompresses the section, instead of copying it, before `main()`.
+    /// After linking, you must compress the program with
+    /// `compress_load_images`, or the `imxrt-rt-compress` program. See
+    /// [compressed load images](Self#compressed-load-images) for more information.
+    ///
+    /// Call this multiple times to compress more sections.
+    pub fn compress(&mut self, section: Section) -> &mut Self {
+        if !self.compressed.contains(&section) {
+            self.compressed.push(section);
+        }
+        self
+    }
+    /// Set a size budget, in bytes, for a section.
+    ///
+    /// If the section is larger than `bytes`, linking fails. Use this to catch
+    /// changes that exceed your memory budget, even if the section still fits
+    /// in its memory. See [size budgets](Self#size-budgets) for more information.
+    ///
+    /// This overrides any previous budget for the same section.
+    pub fn budget(&mut self, section: Section, bytes: usize) -> &mut Self {
+        self.budgets.retain(|(sec, _)| *sec != section);
+        self.budgets.push((section, bytes));
+        self
+    }
+    /// Set a size budget, in bytes, for the flash image.
+    ///
+    /// The flash image spans from the start of flash (or the start of your
+    /// flash partition) to the end of the last section loaded from flash.
+    /// If the image is larger than `bytes`, linking fails.
+    ///
+    /// If this builder is not configuring a flash-loaded runtime, this call
+    /// is silently ignored.
+    pub fn image_budget(&mut self, bytes: usize) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.image_budget = Some(bytes);
+        }
+        self
+    }
+    /// Record the cycle count after each boot step.
+    ///
+    /// See [Boot timestamps](Self#boot-timestamps) for more information.
+    ///
+    /// By default, this is disabled.
+    pub fn boot_timestamps(&mut self, enable: bool) -> &mut Self {
+        self.boot_timestamps = enable;
+        self
+    }
+
+    /// Capture, then clear, the reset status before `main()`.
+    ///
+    /// See [Reset reasons](Self#reset-reasons) for more information.
+    ///
+    /// By default, this is disabled.
+    pub fn reset_reason(&mut self, enable: bool) -> &mut Self {
+        self.reset_reason = enable;
+        self
+    }
+
+    /// Run static constructors before `main()`.
+    ///
+    /// See [static constructors](Self#static-constructors) for more information.
+    ///
+    /// By default, this is disabled.
+    pub fn init_arrays(&mut self, enable: bool) -> &mut Self {
+        self.init_arrays = enable;
+        self
+    }
+
+    /// Define the symbols that newlib and picolibc expect.
+    ///
+    /// See [C libraries](Self#c-libraries) for more information.
+    ///
+    /// By default, this is disabled.
+    pub fn libc_symbols(&mut self, enable: bool) -> &mut Self {
+        self.libc_symbols = enable;
+        self
+    }
+
+    /// Keep the ARM unwind tables, and place them in `memory`.
+    ///
+    /// See [unwind tables](Self#unwind-tables) for more information.
+    ///
+    /// By default, the unwind tables are discarded.
+    pub fn unwind_tables(&mut self, memory: Memory) -> &mut Self {
+        self.unwind_tables = Some(memory);
+        self
+    }
+
+    /// Reserve `bytes` of `memory` for a crash log that survives reset.
+    ///
+    /// See [crash logs](Self#crash-logs) for more information.
+    ///
+    /// By default, there's no crash log.
+    pub fn crash_log(&mut self, memory: Memory, bytes: usize) -> &mut Self {
+        self.crash_log = Some((memory, bytes));
         self
     }
-    /// Set the size, in bytes, of the heap.
-    pub fn heap_size(&mut self, bytes: usize) -> &mut Self {
-        self.heap_size.default = bytes;
+
+    /// Handle faults with the runtime's fault handler, and record faults in `memory`.
+    ///
+    /// `action` describes what happens after the fault is recorded. See
+    /// [fault handler](Self#fault-handler) for more information.
+    ///
+    /// By default, faults are handled by `cortex-m-rt`.
+    pub fn fault_handler(&mut self, memory: Memory, action: FaultAction) -> &mut Self {
+        self.fault_handler = Some((memory, action));
         self
     }
-    /// Let end users override the heap size using an environment variable.
+
+    /// Build a program that runs from any flash partition.
     ///
-    /// See the [environment overrides](Self#environment-overrides) documentation
-    /// for more information.
-    pub fn heap_size_env_override(&mut self, key: impl AsRef<str>) -> &mut Self {
-        self.heap_size.set_env_key(key.as_ref().into());
+    /// See [position-independent images](Self#position-independent-images) for more
+    /// information.
+    ///
+    /// By default, the program only runs from the partition where it's linked.
+    pub fn position_independent(&mut self, enable: bool) -> &mut Self {
+        self.position_independent = enable;
+        self
+    }
+
+    /// Let the boot ROM copy the image into the first `bytes` of `memory`.
+    ///
+    /// See [boot ROM copies](Self#boot-rom-copies) for more information.
+    ///
+    /// By default, the boot ROM executes the image in place. If this builder is not
+    /// configuring a flash-loaded runtime, this call is silently ignored.
+    pub fn rom_copy(&mut self, memory: Memory, bytes: usize) -> &mut Self {
+        if let Some(flash_opts) = &mut self.flash_opts {
+            flash_opts.rom_copy = Some((memory, bytes));
+        }
+        self
+    }
+
+    /// Load the RAM image into the first `bytes` of `memory` with the boot ROM's serial
+    /// downloader.
+    ///
+    /// See [serial download images](Self#serial-download-images) for more information.
+    ///
+    /// By default, a RAM image can only be loaded by a debugger.
+    pub fn serial_download(&mut self, memory: Memory, bytes: usize) -> &mut Self {
+        self.serial_download = Some((memory, bytes));
+        self
+    }
+
+    /// Check the integrity of the flash image before copying from it.
+    ///
+    /// `on_failure` describes what happens when the check fails. See
+    /// [image checks](Self#image-checks) for more information.
+    ///
+    /// By default, the image isn't checked.
+    pub fn image_check(&mut self, on_failure: ImageCheckFailure) -> &mut Self {
+        self.image_check = Some(on_failure);
         self
     }
+
     /// Set the FlexSPI peripheral that interfaces flash.
     ///
     /// See the [`FlexSpi`] to understand the default values.
@@ -699,6 +1830,12 @@ impl RuntimeBuilder {
     /// - uninit
     /// - stack
     /// - heap
+    /// - named heaps
+    ///
+    /// Returns an error if a named heap's name isn't an ASCII identifier.
+    ///
+    /// Returns an error if [remainder sizing](Self#remainder-sizing) would place
+    /// other sections behind the region that consumes the remainder of its memory.
     ///
     /// The implementation may rely on the _linker_ to signal other errors.
     /// For example, suppose a runtime configuration with no ITCM banks. If a
@@ -720,110 +1857,898 @@ impl RuntimeBuilder {
         Ok(())
     }
 
-    /// Write the generated linker script into the provided writer.
-    ///
-    /// Use this if you want more control over where the generated linker script
-    /// ends up. Otherwise, you should prefer [`build()`](Self::build) for an
-    /// easier experience.
+    /// Write the generated linker script into the provided writer.
+    ///
+    /// Use this if you want more control over where the generated linker script
+    /// ends up. Otherwise, you should prefer [`build()`](Self::build) for an
+    /// easier experience.
+    ///
+    /// Unlike `build()`, this method does not ensure that the linker script is
+    /// available to the linker. Additionally, this method does not consider
+    /// the value set by [`linker_script_name`](Self::linker_script_name).
+    ///
+    /// # Errors
+    ///
+    /// See [`build()`](Self::build) to understand the possible errors.
+    fn write_linker_script(
+        &self,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(requirements) = self.flexram_fit {
+            let banks =
+                self.family
+                    .fit_flexram_banks(requirements, self.flexram_ecc, self.mecc64)?;
+            return self
+                .clone()
+                .flexram_banks(banks)
+                .write_linker_script(writer);
+        }
+
+        self.check_configurations()?;
+
+        if let Some(flash_opts) = &self.flash_opts {
+            write_flash_memory_map(
+                writer,
+                self.family,
+                flash_opts,
+                &self.flexram_layout,
+                self.flexram_ecc,
+                self.mecc64,
+                self.text_spill,
+            )?;
+
+            if flash_opts.boot_header {
+                let boot_header_x = match self.family {
+                    Family::Imxrt1010
+                    | Family::Imxrt1015
+                    | Family::Imxrt1020
+                    | Family::Imxrt1040
+                    | Family::Imxrt1050
+                    | Family::Imxrt1060
+                    | Family::Imxrt1064
+                    | Family::Imxrt1160
+                    | Family::Imxrt1170 => include_bytes!("host/imxrt-boot-header.x").as_slice(),
+                    Family::Imxrt1180 => include_bytes!("host/imxrt-boot-header-1180.x").as_slice(),
+                };
+                writer.write_all(boot_header_x)?;
+                // The 1180's boot header describes its own image.
+                if self.family != Family::Imxrt1180 {
+                    self.write_image_size(writer)?;
+                }
+            }
+        } else {
+            write_ram_memory_map(
+                writer,
+                self.family,
+                &self.flexram_layout,
+                self.flexram_ecc,
+                self.mecc64,
+                self.serial_download,
+            )?;
+
+            if self.serial_download.is_some() {
+                writer.write_all(include_bytes!("host/imxrt-sdp-header.x"))?;
+                self.write_image_size(writer)?;
+            }
+        }
+        write_ecc_zero_spans(
+            writer,
+            self.family,
+            &self.flexram_layout,
+            self.flexram_ecc,
+            self.mecc64,
+            self.crash_log
+                .map(|(memory, _)| memory)
+                .filter(|memory| self.has_ecc(*memory)),
+        )?;
+
+        if !self.compressed.is_empty() {
+            writeln!(writer, "MEMORY {{")?;
+            writeln!(
+                writer,
+                "LZ4 (R) : ORIGIN = {LZ4_STAGING_ORIGIN:#X}, LENGTH = {LZ4_STAGING_LENGTH:#X}"
+            )?;
+            writeln!(writer, "}}")?;
+        }
+
+        if cfg!(feature = "device") {
+            writeln!(writer, "INCLUDE {}", self.device_script_name)?;
+        }
+
+        // Keep these alias names in sync with the primary linker script.
+        // The main linker script uses these region aliases for placing
+        // sections. Then, the user specifies the actual placement through
+        // the builder. This saves us the step of actually generating SECTION
+        // commands.
+        if self.text_spill.is_some() {
+            writeln!(writer, "REGION_ALIAS(\"REGION_TEXT\", ITCM_TEXT);")?;
+        } else {
+            region_alias(writer, "TEXT", self.text)?;
+        }
+        region_alias(writer, "VTABLE", self.vectors)?;
+        region_alias(writer, "RODATA", self.rodata)?;
+        region_alias(writer, "DATA", self.data)?;
+        region_alias(writer, "BSS", self.bss)?;
+        region_alias(writer, "UNINIT", self.uninit)?;
+
+        region_alias(writer, "STACK", self.stack)?;
+        region_alias(writer, "HEAP", self.heap)?;
+        // Used in the linker script and / or target code.
+        let stack_size = RegionSize::new(&self.stack_size, self.stack_remainder)?;
+        let heap_size = RegionSize::new(&self.heap_size, self.heap_remainder)?;
+        self.check_remainders(stack_size, heap_size)?;
+        writeln!(writer, "__stack_size = {:#010X};", stack_size.bytes())?;
+        writeln!(writer, "__heap_size = {:#010X};", heap_size.bytes())?;
+        writeln!(
+            writer,
+            "__heap_remainder = {};",
+            heap_size.is_remainder() as u32
+        )?;
+
+        if self.has_flash() {
+            // Runtime will see different VMA and LMA, and copy the sections.
+            region_alias(writer, "LOAD_VTABLE", Memory::Flash)?;
+            region_alias(writer, "LOAD_TEXT", Memory::Flash)?;
+            // Compressed sections are staged until they're compressed into flash.
+            for (name, section) in [
+                ("LOAD_TEXT_COPY", Section::Text),
+                ("LOAD_RODATA", Section::Rodata),
+                ("LOAD_DATA", Section::Data),
+            ] {
+                if self.compressed.contains(&section) {
+                    writeln!(writer, "REGION_ALIAS(\"REGION_{name}\", LZ4);")?;
+                } else {
+                    region_alias(writer, name, Memory::Flash)?;
+                }
+            }
+        } else {
+            // When the VMA and LMA are equal, the runtime performs no copies.
+            region_alias(writer, "LOAD_VTABLE", self.vectors)?;
+            region_alias(writer, "LOAD_TEXT", self.text)?;
+            region_alias(writer, "LOAD_TEXT_COPY", self.text)?;
+            region_alias(writer, "LOAD_RODATA", self.rodata)?;
+            region_alias(writer, "LOAD_DATA", self.data)?;
+        }
+        // The runtime decompresses these sections, instead of copying them.
+        for section in [Section::Text, Section::Rodata, Section::Data] {
+            writeln!(
+                writer,
+                "__{}_lz4 = {};",
+                &section.to_string()[1..],
+                self.compressed.contains(&section) as u32
+            )?;
+        }
+
+        if self.image_check.is_some() {
+            writeln!(
+                writer,
+                "ASSERT(SIZEOF(.image_check) == {IMAGE_CHECK_RECORD_SIZE}, \"ERROR(imxrt-rt): the image check record has an unexpected size.\");"
+            )?;
+        }
+        if self.image_check == Some(ImageCheckFailure::Handler) {
+            writeln!(writer, "EXTERN(ImageCheckFailed);")?;
+            writeln!(
+                writer,
+                "ASSERT(ImageCheckFailed >= ADDR(.xip) && ImageCheckFailed < ADDR(.xip) + SIZEOF(.xip), \"\nERROR(imxrt-rt): ImageCheckFailed is not placed in .xip.\nThe image check runs before .text is copied. Place ImageCheckFailed in the .xip\nlink section.\");"
+            )?;
+        }
+
+        if self.position_independent {
+            writeln!(
+                writer,
+                "ASSERT(__imxrt_rt_exip_items == __imxrt_rt_sxip_items, \"\nERROR(imxrt-rt): A position-independent image can't place items in flash.\nItems in .xip, items placed with #[imxrt_rt::flash], and crates placed in flash\nrun at their linked address. Place them in RAM.\");"
+            )?;
+        }
+
+        if self.libc_symbols {
+            for (symbol, alias) in LIBC_SYMBOLS {
+                writeln!(writer, "PROVIDE({symbol} = {alias});")?;
+            }
+            writeln!(writer, "EXTERN(__imxrt_rt_sbrk);")?;
+            writeln!(writer, "PROVIDE(_sbrk = __imxrt_rt_sbrk);")?;
+        }
+
+        if let Some((_, action)) = self.fault_handler {
+            writeln!(writer, "EXTERN(__imxrt_rt_fault);")?;
+            for exception in ["HardFault", "MemoryManagement", "BusFault", "UsageFault"] {
+                writeln!(writer, "PROVIDE({exception} = __imxrt_rt_fault);")?;
+            }
+            if action == FaultAction::Handler {
+                writeln!(writer, "EXTERN(FaultRecorded);")?;
+                writeln!(writer, "__imxrt_rt_fault_hook = FaultRecorded;")?;
+            } else {
+                writeln!(writer, "__imxrt_rt_fault_hook = 0;")?;
+            }
+        }
+
+        // Referenced in target code.
+        writeln!(
+            writer,
+            "__boot_timestamps = {};",
+            self.boot_timestamps as u32
+        )?;
+        writeln!(writer, "__reset_reason = {};", self.reset_reason as u32)?;
+        writeln!(writer, "__init_arrays = {};", self.init_arrays as u32)?;
+        writeln!(
+            writer,
+            "__position_independent = {};",
+            self.position_independent as u32
+        )?;
+        writeln!(
+            writer,
+            "__flexram_config = {:#010X};",
+            flexram_config(self.family, &self.flexram_layout)
+        )?;
+
+        // The target runtime looks at this value to predicate some pre-init instructions.
+        // Could be helpful for binary identification, but it's an undocumented feature.
+        writeln!(writer, "__imxrt_rt_v0.2 = {:#010X};", self.family.id(),)?;
+
+        // Describes the memory regions for the memory report.
+        for memory in self.memories() {
+            if memory == Memory::Itcm && self.text_spill.is_some() {
+                writeln!(writer, "__imxrt_rt_ITCM_origin = ORIGIN(ITCM_TEXT);")?;
+                writeln!(
+                    writer,
+                    "__imxrt_rt_ITCM_length = LENGTH(ITCM_TEXT) + LENGTH(ITCM);"
+                )?;
+                continue;
+            }
+            writeln!(writer, "__imxrt_rt_{memory}_origin = ORIGIN({memory});")?;
+            writeln!(writer, "__imxrt_rt_{memory}_length = LENGTH({memory});")?;
+        }
+
+        self.write_link_x(writer)?;
+
+        Ok(())
+    }
+
+    /// Define the size of the image, from the start of the FLASH region through
+    /// the end of its last load image.
+    ///
+    /// The boot data tells the boot ROM how many bytes to load.
+    fn write_image_size(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let placements = self.placements();
+        let end = end_of(&flash_placements(&placements), "LOADADDR")
+            .expect("There's always a vector table");
+        writeln!(writer, "__image_size = {end} - ORIGIN(FLASH);")
+    }
+
+    /// Write the primary linker script.
+    ///
+    /// The primary linker script has placeholders for content that depends
+    /// on the runtime configuration. A placeholder is a line that looks like
+    ///
+    /// ```text
+    /// /* imxrt-rt-generated(name) */
+    /// ```
+    ///
+    /// The placeholder is kept in the output, and the generated content
+    /// is written after it.
+    fn write_link_x(&self, writer: &mut dyn Write) -> Result<(), Box<dyn std::error::Error>> {
+        const LINK_X: &str = include_str!("host/imxrt-link.x");
+        for line in LINK_X.split_inclusive('\n') {
+            writer.write_all(line.as_bytes())?;
+            let placeholder = line
+                .trim()
+                .strip_prefix("/* imxrt-rt-generated(")
+                .and_then(|line| line.strip_suffix(") */"));
+            match placeholder {
+                None => {}
+                Some("stack") => {
+                    let stack_size = RegionSize::new(&self.stack_size, self.stack_remainder)?;
+                    if !stack_size.is_remainder() {
+                        write_stack(writer, stack_size)?;
+                    }
+                }
+                Some("stack_remainder") => {
+                    let stack_size = RegionSize::new(&self.stack_size, self.stack_remainder)?;
+                    if stack_size.is_remainder() {
+                        write_stack(writer, stack_size)?;
+                    }
+                }
+                Some("heaps") => self.write_named_heaps(writer)?,
+                Some("flash_crates") => self.write_crate_code(writer, Memory::Flash)?,
+                Some("text_spill") => self.write_text_spill(writer)?,
+                Some("relocated") => self.write_relocated_sections(writer)?,
+                Some("copy_table") => self.write_copy_table(writer)?,
+                Some("region_checks") => self.write_region_checks(writer)?,
+                Some("budgets") => self.write_budgets(writer)?,
+                Some("lz4") => self.write_lz4(writer)?,
+                Some("image_check") => self.write_image_check(writer)?,
+                Some("crash_log") => self.write_crash_log(writer)?,
+                Some("fault_record") => self.write_fault_record(writer)?,
+                Some("unwind_tables") => self.write_unwind_tables(writer)?,
+                Some("got") => self.write_got(writer)?,
+                Some("discard_unwind_tables") => self.write_discard_unwind_tables(writer)?,
+                Some(name) => unreachable!("Unknown placeholder '{name}' in imxrt-link.x"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate the sections for all named heaps.
+    fn write_named_heaps(&self, writer: &mut dyn Write) -> Result<(), Box<dyn std::error::Error>> {
+        for heap in &self.named_heaps {
+            let NamedHeap { name, memory, .. } = heap;
+            writeln!(writer, "  .heap.{name} (NOLOAD) : ALIGN(4)")?;
+            writeln!(writer, "  {{")?;
+            writeln!(writer, "    __sheap_{name} = .;")?;
+            writeln!(writer, "    . += ALIGN({:#010X}, 4);", heap.size.read()?)?;
+            writeln!(writer, "    __eheap_{name} = .;")?;
+            writeln!(writer, "  }} > {memory}")?;
+        }
+        Ok(())
+    }
+
+    /// Generate the sections for items that are relocated into RAM.
+    ///
+    /// These sections hold the items placed by attribute macros, like
+    /// `#[imxrt_rt::itcm]`. The ITCM section also holds the ITCM functions.
+    fn write_relocated_sections(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let memories = self.memories();
+        for (memory, section) in RELOCATED_SECTIONS {
+            writeln!(writer, "  {section} : ALIGN(4)")?;
+            writeln!(writer, "  {{")?;
+            writeln!(writer, "    FILL(0xff);")?;
+            writeln!(writer, "    *(.imxrt_rt{section} .imxrt_rt{section}.*)")?;
+            self.write_crate_code(writer, memory)?;
+            if memory == Memory::Itcm {
+                for pattern in &self.itcm_functions {
+                    writeln!(writer, "    *(.text.{pattern})")?;
+                }
+            }
+            writeln!(writer, "    . = ALIGN(4);")?;
+            if !memories.contains(&memory) {
+                // Expected to be empty. See the region checks.
+                writeln!(writer, "  }} > REGION_LOAD_TEXT")?;
+            } else if self.has_flash() {
+                writeln!(writer, "  }} > {memory} AT> FLASH")?;
+            } else {
+                writeln!(writer, "  }} > {memory}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate the crash log section.
+    ///
+    /// Without a crash log, the symbols are zero, and the target has no crash log.
+    fn write_crash_log(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let Some((memory, bytes)) = self.crash_log else {
+            writeln!(writer, "  __scrashlog = 0;")?;
+            writeln!(writer, "  __ecrashlog = 0;")?;
+            return Ok(());
+        };
+        writeln!(writer, "  .crashlog (NOLOAD) : ALIGN(8)")?;
+        writeln!(writer, "  {{")?;
+        writeln!(writer, "    __scrashlog = .;")?;
+        writeln!(writer, "    . += ALIGN({bytes:#X}, 8);")?;
+        writeln!(writer, "    __ecrashlog = .;")?;
+        writeln!(writer, "  }} > {memory}")?;
+        Ok(())
+    }
+
+    /// Generate the fault handler's record.
+    ///
+    /// Without a fault handler, the symbol is zero, and the target has no record.
+    fn write_fault_record(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let Some((memory, _)) = self.fault_handler else {
+            writeln!(writer, "  __imxrt_rt_fault_record = 0;")?;
+            return Ok(());
+        };
+        writeln!(writer, "  .fault_record (NOLOAD) : ALIGN(8)")?;
+        writeln!(writer, "  {{")?;
+        writeln!(writer, "    __imxrt_rt_fault_record = .;")?;
+        writeln!(writer, "    . += {FAULT_RECORD_SIZE};")?;
+        writeln!(writer, "  }} > {memory}")?;
+        Ok(())
+    }
+
+    /// Generate the global offset table of a position-independent image.
+    ///
+    /// Pre-init moves the entries that point into flash. Otherwise, the symbols
+    /// are zero, and the linker script requires that there's no table.
+    fn write_got(&self, writer: &mut dyn Write) -> io::Result<()> {
+        if !self.position_independent {
+            for symbol in ["sgot", "egot", "sflash", "eflash"] {
+                writeln!(writer, "  __imxrt_rt_{symbol} = 0;")?;
+            }
+            return Ok(());
+        }
+        writeln!(writer, "  .imxrt_rt.got : ALIGN(4)")?;
+        writeln!(writer, "  {{")?;
+        writeln!(writer, "    __imxrt_rt_sgot = .;")?;
+        writeln!(writer, "    *(.got .got.*);")?;
+        writeln!(writer, "    __imxrt_rt_egot = .;")?;
+        writeln!(writer, "  }} > REGION_DATA AT> FLASH")?;
+        writeln!(writer, "  __imxrt_rt_sflash = ORIGIN(FLASH);")?;
+        writeln!(
+            writer,
+            "  __imxrt_rt_eflash = ORIGIN(FLASH) + LENGTH(FLASH);"
+        )?;
+        Ok(())
+    }
+
+    /// Generate the sections that keep the ARM unwind tables.
+    ///
+    /// The linker discards the tables before it places any sections, so the
+    /// tables are only discarded if they're not kept.
+    fn write_unwind_tables(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let Some(memory) = self.unwind_tables else {
+            return Ok(());
+        };
+        let region = if memory != Memory::Flash && self.has_flash() {
+            format!("{memory} AT> FLASH")
+        } else {
+            memory.to_string()
+        };
+        writeln!(writer, "  .ARM.extab : ALIGN(4)")?;
+        writeln!(writer, "  {{")?;
+        writeln!(writer, "    *(.ARM.extab .ARM.extab.*)")?;
+        writeln!(writer, "    . = ALIGN(4);")?;
+        writeln!(writer, "  }} > {region}")?;
+        writeln!(writer, "  .ARM.exidx : ALIGN(4)")?;
+        writeln!(writer, "  {{")?;
+        writeln!(writer, "    __exidx_start = .;")?;
+        writeln!(writer, "    *(.ARM.exidx .ARM.exidx.*)")?;
+        writeln!(writer, "    __exidx_end = .;")?;
+        writeln!(writer, "  }} > {region}")?;
+        Ok(())
+    }
+
+    /// Generate the input section descriptions that discard the ARM unwind tables.
+    fn write_discard_unwind_tables(&self, writer: &mut dyn Write) -> io::Result<()> {
+        if self.unwind_tables.is_none() {
+            writeln!(writer, "    *(.ARM.exidx);")?;
+            writeln!(writer, "    *(.ARM.exidx.*);")?;
+            writeln!(writer, "    *(.ARM.extab.*);")?;
+        }
+        Ok(())
+    }
+
+    /// Generate the input section descriptions for crates placed in `memory`.
+    fn write_crate_code(&self, writer: &mut dyn Write, memory: Memory) -> io::Result<()> {
+        for (name, _) in self.crate_code.iter().filter(|(_, mem)| *mem == memory) {
+            writeln!(writer, "    *lib{name}-*.rlib:*(.text .text.*)")?;
+        }
+        Ok(())
+    }
+
+    /// Generate the flash section that receives .text when it exceeds its ITCM limit.
+    ///
+    /// The linker only spills sections when it's asked to place input sections in
+    /// non-contiguous regions. Otherwise, this section is empty.
+    fn write_text_spill(&self, writer: &mut dyn Write) -> io::Result<()> {
+        if self.text_spill.is_none() {
+            return Ok(());
+        }
+        writeln!(writer, "  .text_spill : ALIGN(4)")?;
+        writeln!(writer, "  {{")?;
+        writeln!(writer, "    FILL(0xff);")?;
+        writeln!(writer, "    *(.text .text.*);")?;
+        writeln!(writer, "    *(.HardFaultTrampoline);")?;
+        writeln!(writer, "    *(.HardFault.*);")?;
+        writeln!(writer, "    . = ALIGN(4);")?;
+        writeln!(writer, "  }} > FLASH")?;
+        Ok(())
+    }
+
+    /// Generate the records for all sections that are copied by the copy table.
     ///
-    /// Unlike `build()`, this method does not ensure that the linker script is
-    /// available to the linker. Additionally, this method does not consider
-    /// the value set by [`linker_script_name`](Self::linker_script_name).
+    /// Each record is the section's start address, end address, and load address.
+    fn write_copy_table(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let memories = self.memories();
+        for (_, section) in RELOCATED_SECTIONS
+            .into_iter()
+            .filter(|(memory, _)| memories.contains(memory))
+        {
+            writeln!(writer, "    LONG(ADDR({section}));")?;
+            writeln!(writer, "    LONG(ADDR({section}) + SIZEOF({section}));")?;
+            writeln!(writer, "    LONG(LOADADDR({section}));")?;
+        }
+        if self
+            .unwind_tables
+            .is_some_and(|memory| memory != Memory::Flash)
+        {
+            for section in UNWIND_SECTIONS {
+                writeln!(writer, "    LONG(ADDR({section}));")?;
+                writeln!(writer, "    LONG(ADDR({section}) + SIZEOF({section}));")?;
+                writeln!(writer, "    LONG(LOADADDR({section}));")?;
+            }
+        }
+        // A position-independent image copies everything that has a load image.
+        if self.position_independent {
+            let sections = [
+                (".vector_table", None),
+                (".text", Some(Section::Text)),
+                (".rodata", Some(Section::Rodata)),
+                (".imxrt_rt.got", None),
+                (".data", Some(Section::Data)),
+            ];
+            for (section, _) in sections.into_iter().filter(|(_, compressed)| {
+                compressed.is_none_or(|section| !self.compressed.contains(&section))
+            }) {
+                writeln!(writer, "    LONG(ADDR({section}));")?;
+                writeln!(writer, "    LONG(ADDR({section}) + SIZEOF({section}));")?;
+                writeln!(writer, "    LONG(LOADADDR({section}));")?;
+            }
+        }
+        // Each LOADADDR is in the staging region. compress_load_images
+        // rewrites these records to describe the compressed images.
+        for section in &self.compressed {
+            writeln!(writer, "    LONG(ADDR({section}));")?;
+            writeln!(writer, "    LONG(ADDR({section}) + SIZEOF({section}));")?;
+            writeln!(writer, "    LONG(LOADADDR({section}));")?;
+        }
+        Ok(())
+    }
+
+    /// Mark where compressed load images start in flash.
+    fn write_lz4(&self, writer: &mut dyn Write) -> io::Result<()> {
+        if self.compressed.is_empty() {
+            return Ok(());
+        }
+        let placements = self.placements();
+        let end = end_of(&flash_placements(&placements), "LOADADDR").expect("Flash holds .xip");
+        writeln!(writer, "__imxrt_rt_lz4_start = ALIGN({end}, 4);")?;
+        Ok(())
+    }
+
+    /// Generate the image check record.
     ///
-    /// # Errors
+    /// The record precedes the rest of the image, except for the vector table.
+    /// `checksum_image` writes the end of the image and its CRC after linking.
+    fn write_image_check(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let Some(on_failure) = self.image_check else {
+            writeln!(writer, "  __imxrt_rt_image_check = 0;")?;
+            return Ok(());
+        };
+        let action = match on_failure {
+            ImageCheckFailure::Spin => "0",
+            ImageCheckFailure::Reset => "1",
+            ImageCheckFailure::Handler => "ImageCheckFailed",
+        };
+        writeln!(writer, "  .image_check : ALIGN(4)")?;
+        writeln!(writer, "  {{")?;
+        writeln!(writer, "    __imxrt_rt_image_check = .;")?;
+        writeln!(writer, "    LONG({action});")?;
+        writeln!(writer, "    LONG(LOADADDR(.vector_table)); /* Start */")?;
+        writeln!(writer, "    LONG(0); /* End, written after linking */")?;
+        writeln!(writer, "    LONG(0); /* CRC-32, written after linking */")?;
+        for entries in crc32_table().chunks(4) {
+            let longs: Vec<String> = entries
+                .iter()
+                .map(|entry| format!("LONG({entry:#010X});"))
+                .collect();
+            writeln!(writer, "    {}", longs.join(" "))?;
+        }
+        writeln!(writer, "  }} > REGION_LOAD_TEXT")?;
+        Ok(())
+    }
+
+    /// Describe where the linker script places each output section.
+    fn placements(&self) -> Vec<Placement> {
+        let load_text = if self.has_flash() {
+            Memory::Flash
+        } else {
+            self.text
+        };
+        let mut placements = vec![Placement::new(".stack", self.stack, Some("stack"), false)];
+        if let Some((memory, _)) = self.crash_log {
+            placements.push(Placement::new(
+                ".crashlog",
+                memory,
+                Some("crash_log"),
+                false,
+            ));
+        }
+        if let Some((memory, _)) = self.fault_handler {
+            placements.push(Placement::new(
+                ".fault_record",
+                memory,
+                Some("fault_handler"),
+                false,
+            ));
+        }
+        placements.push(Placement::new(
+            ".vector_table",
+            self.vectors,
+            Some("vectors"),
+            true,
+        ));
+        if self.image_check.is_some() {
+            placements.push(Placement::new(".image_check", Memory::Flash, None, false));
+        }
+        placements.push(Placement::new(".xip", load_text, None, true));
+        let memories = self.memories();
+        placements.extend(
+            RELOCATED_SECTIONS
+                .into_iter()
+                .filter(|(memory, _)| memories.contains(memory))
+                .map(|(memory, section)| Placement::new(section, memory, None, true)),
+        );
+        placements.extend([
+            Placement::new(".copy_table", load_text, None, true),
+            Placement::new(
+                ".text",
+                self.text,
+                Some("text"),
+                !self.compressed.contains(&Section::Text),
+            ),
+        ]);
+        if self.text_spill.is_some() {
+            placements.push(Placement::new(".text_spill", Memory::Flash, None, false));
+        }
+        placements.push(Placement::new(
+            ".rodata",
+            self.rodata,
+            Some("rodata"),
+            !self.compressed.contains(&Section::Rodata),
+        ));
+        if let Some(memory) = self.unwind_tables {
+            placements.extend(
+                UNWIND_SECTIONS
+                    .map(|section| Placement::new(section, memory, Some("unwind_tables"), true)),
+            );
+        }
+        if self.position_independent {
+            placements.push(Placement::new(
+                ".imxrt_rt.got",
+                self.data,
+                Some("data"),
+                true,
+            ));
+        }
+        placements.extend([
+            Placement::new(
+                ".data",
+                self.data,
+                Some("data"),
+                !self.compressed.contains(&Section::Data),
+            ),
+            Placement::new(".bss", self.bss, Some("bss"), false),
+            Placement::new(".uninit", self.uninit, Some("uninit"), false),
+            Placement::new(".heap", self.heap, Some("heap"), false),
+        ]);
+        for heap in &self.named_heaps {
+            placements.push(Placement::new(
+                format!(".heap.{}", heap.name),
+                heap.memory,
+                Some("named_heap"),
+                false,
+            ));
+        }
+        placements
+    }
+
+    /// Returns `true` if the linker script has a FLASH region that holds load images.
     ///
-    /// See [`build()`](Self::build) to understand the possible errors.
-    fn write_linker_script(
-        &self,
-        writer: &mut dyn Write,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.check_configurations()?;
+    /// A serial download image's FLASH region is the image that's loaded into RAM.
+    fn has_flash(&self) -> bool {
+        self.flash_opts.is_some() || self.serial_download.is_some()
+    }
 
-        if let Some(flash_opts) = &self.flash_opts {
-            write_flash_memory_map(
-                writer,
+    /// The start address of a memory that's defined in the linker script.
+    ///
+    /// A flash image that's copied by the boot ROM runs from its copy in RAM.
+    fn memory_origin(&self, memory: Memory) -> usize {
+        let ram = |memory| {
+            // Only DTCM has no region for a boot ROM image. It always starts here.
+            rom_image_region(
                 self.family,
-                flash_opts,
+                memory,
                 &self.flexram_layout,
                 self.flexram_ecc,
                 self.mecc64,
-            )?;
+            )
+            .map_or(0x2000_0000, |region| region.start)
+        };
+        match (memory, &self.flash_opts, self.serial_download) {
+            (Memory::Flash, Some(flash_opts), _) => match flash_opts.rom_copy {
+                Some((memory, _)) => ram(memory),
+                None => flash_opts
+                    .flash_origin(self.family)
+                    .expect("Already checked") as usize,
+            },
+            (Memory::Flash, None, Some((memory, _))) => ram(memory),
+            (Memory::Flash, None, None) => unreachable!("There's no flash"),
+            (Memory::Itcm | Memory::Dtcm | Memory::Ocram, _, _) => ram(memory),
+        }
+    }
 
-            if flash_opts.boot_header {
-                let boot_header_x = match self.family {
-                    Family::Imxrt1010
-                    | Family::Imxrt1015
-                    | Family::Imxrt1020
-                    | Family::Imxrt1040
-                    | Family::Imxrt1050
-                    | Family::Imxrt1060
-                    | Family::Imxrt1064
-                    | Family::Imxrt1160
-                    | Family::Imxrt1170 => include_bytes!("host/imxrt-boot-header.x").as_slice(),
-                    Family::Imxrt1180 => include_bytes!("host/imxrt-boot-header-1180.x").as_slice(),
-                };
-                writer.write_all(boot_header_x)?;
+    /// Returns `true` if the memory is RAM that's protected by ECC.
+    fn has_ecc(&self, memory: Memory) -> bool {
+        let ecc = match memory {
+            Memory::Ocram => self.flexram_ecc.is_enable() || self.mecc64.is_enable(),
+            Memory::Itcm | Memory::Dtcm => self.flexram_ecc.is_enable(),
+            Memory::Flash => false,
+        };
+        ecc && self.memories().contains(&memory)
+    }
+
+    /// The memories that are defined in the linker script.
+    fn memories(&self) -> Vec<Memory> {
+        let mut memories = Vec::new();
+        if self.has_flash() {
+            memories.push(Memory::Flash);
+        }
+        if layout_count_of(FlexRamKind::Itcm, &self.flexram_layout) > 0 {
+            memories.push(Memory::Itcm);
+        }
+        if layout_count_of(FlexRamKind::Dtcm, &self.flexram_layout) > 0 {
+            memories.push(Memory::Dtcm);
+        }
+        let ocram_count = layout_count_of(FlexRamKind::Ocram, &self.flexram_layout);
+        if self
+            .family
+            .ocram_start_size(ocram_count, self.flexram_ecc, self.mecc64)
+            .size
+            > 0
+        {
+            memories.push(Memory::Ocram);
+        }
+        memories
+    }
+
+    /// Generate assertions that explain how to resolve a memory overflow.
+    ///
+    /// The linker will also signal these overflows. But the linker doesn't
+    /// know how the sections were placed, so it can't suggest a solution.
+    fn write_region_checks(&self, writer: &mut dyn Write) -> io::Result<()> {
+        for (memory, section) in RELOCATED_SECTIONS {
+            if !self.memories().contains(&memory) {
+                writeln!(
+                    writer,
+                    "ASSERT(SIZEOF({section}) == 0, \"\nERROR(imxrt-rt): Items are placed in {memory}, but there's no {memory}.\");"
+                )?;
             }
-        } else {
-            write_ram_memory_map(
+        }
+        if let Some(limit) = self.text_spill {
+            writeln!(
                 writer,
-                self.family,
-                &self.flexram_layout,
-                self.flexram_ecc,
-                self.mecc64,
+                "ASSERT(ADDR(.text) + SIZEOF(.text) <= ORIGIN(ITCM_TEXT) + LENGTH(ITCM_TEXT), \"\nERROR(imxrt-rt): .text exceeds its ITCM limit of {limit} bytes.\nLink with --enable-non-contiguous-regions to spill .text into flash.\");"
             )?;
         }
-
-        if cfg!(feature = "device") {
-            writeln!(writer, "INCLUDE {}", self.device_script_name)?;
+        let placements = self.placements();
+        for memory in self.memories() {
+            let (placements, end): (Vec<&Placement>, &str) = if memory == Memory::Flash {
+                (flash_placements(&placements), "LOADADDR")
+            } else {
+                (
+                    placements.iter().filter(|p| p.memory == memory).collect(),
+                    "ADDR",
+                )
+            };
+            let Some(used) = end_of(&placements, end) else {
+                continue;
+            };
+            let sections: Vec<&str> = placements.iter().map(|p| p.section.as_str()).collect();
+            let mut message = vec![
+                format!("ERROR(imxrt-rt): {memory} overflows."),
+                format!("{memory} holds {}.", sections.join(", ")),
+                "Possible solutions:".into(),
+            ];
+            if memory == Memory::Flash {
+                message.push(
+                    "- Check the flash size given to RuntimeBuilder::from_flexspi or RuntimeBuilder::in_flash."
+                        .into(),
+                );
+                message.push("- Reduce the size of your program.".into());
+            } else {
+                message.push(format!(
+                    "- Allocate more FlexRAM banks to {memory} with RuntimeBuilder::flexram_banks."
+                ));
+                message.extend(placements.iter().flat_map(|placement| {
+                    placement.suggestion().map(|suggestion| {
+                        format!(
+                            "- Move {} out of {memory} with {suggestion}.",
+                            placement.section
+                        )
+                    })
+                }));
+            }
+            writeln!(
+                writer,
+                "ASSERT({used} <= ORIGIN({memory}) + LENGTH({memory}), \"\n{}\");",
+                message.join("\n")
+            )?;
         }
+        Ok(())
+    }
 
-        // Keep these alias names in sync with the primary linker script.
-        // The main linker script uses these region aliases for placing
-        // sections. Then, the user specifies the actual placement through
-        // the builder. This saves us the step of actually generating SECTION
-        // commands.
-        region_alias(writer, "TEXT", self.text)?;
-        region_alias(writer, "VTABLE", self.vectors)?;
-        region_alias(writer, "RODATA", self.rodata)?;
-        region_alias(writer, "DATA", self.data)?;
-        region_alias(writer, "BSS", self.bss)?;
-        region_alias(writer, "UNINIT", self.uninit)?;
-
-        region_alias(writer, "STACK", self.stack)?;
-        region_alias(writer, "HEAP", self.heap)?;
-        // Used in the linker script and / or target code.
-        writeln!(writer, "__stack_size = {:#010X};", self.stack_size.read()?)?;
-        writeln!(writer, "__heap_size = {:#010X};", self.heap_size.read()?)?;
-
-        if self.flash_opts.is_some() {
-            // Runtime will see different VMA and LMA, and copy the sections.
-            region_alias(writer, "LOAD_VTABLE", Memory::Flash)?;
-            region_alias(writer, "LOAD_TEXT", Memory::Flash)?;
-            region_alias(writer, "LOAD_RODATA", Memory::Flash)?;
-            region_alias(writer, "LOAD_DATA", Memory::Flash)?;
-        } else {
-            // When the VMA and LMA are equal, the runtime performs no copies.
-            region_alias(writer, "LOAD_VTABLE", self.vectors)?;
-            region_alias(writer, "LOAD_TEXT", self.text)?;
-            region_alias(writer, "LOAD_RODATA", self.rodata)?;
-            region_alias(writer, "LOAD_DATA", self.data)?;
+    /// Generate assertions for all section and image budgets.
+    fn write_budgets(&self, writer: &mut dyn Write) -> io::Result<()> {
+        for (section, bytes) in &self.budgets {
+            writeln!(
+                writer,
+                "ASSERT(SIZEOF({section}) <= {bytes:#X}, \"\nERROR(imxrt-rt): {section} exceeds its budget of {bytes} bytes.\");"
+            )?;
         }
+        if let Some(bytes) = self.flash_opts.as_ref().and_then(|opts| opts.image_budget) {
+            let placements = self.placements();
+            if let Some(end) = end_of(&flash_placements(&placements), "LOADADDR") {
+                writeln!(
+                    writer,
+                    "ASSERT({end} - ORIGIN(FLASH) <= {bytes:#X}, \"\nERROR(imxrt-rt): The flash image exceeds its budget of {bytes} bytes.\");"
+                )?;
+            }
+        }
+        Ok(())
+    }
 
-        // Referenced in target code.
-        writeln!(
-            writer,
-            "__flexram_config = {:#010X};",
-            flexram_config(self.family, &self.flexram_layout)
-        )?;
-
-        // The target runtime looks at this value to predicate some pre-init instructions.
-        // Could be helpful for binary identification, but it's an undocumented feature.
-        writeln!(writer, "__imxrt_rt_v0.2 = {:#010X};", self.family.id(),)?;
+    /// Make sure that a region consuming the remainder of its memory
+    /// isn't followed by other sections in that memory.
+    fn check_remainders(
+        &self,
+        stack_size: RegionSize,
+        heap_size: RegionSize,
+    ) -> Result<(), String> {
+        if stack_size.is_remainder() && heap_size.is_remainder() && self.stack == self.heap {
+            return Err(format!(
+                "The stack and heap cannot both consume the remainder of {}",
+                self.stack
+            ));
+        }
+        if heap_size.is_remainder()
+            && let Some(heap) = self
+                .named_heaps
+                .iter()
+                .find(|heap| heap.memory == self.heap)
+        {
+            return Err(format!(
+                "The heap consumes the remainder of {}, so there's no space for heap '{}'",
+                self.heap, heap.name
+            ));
+        }
+        Ok(())
+    }
 
-        let link_x = include_bytes!("host/imxrt-link.x");
-        writer.write_all(link_x)?;
+    /// Check that `memory` is RAM that keeps its contents across reset.
+    ///
+    /// `what` names the item that's placed in the memory.
+    fn check_retained(&self, what: &str, memory: Memory) -> Result<(), String> {
+        if memory == Memory::Flash || !self.memories().contains(&memory) {
+            return Err(format!(
+                "{what} cannot be placed in {memory}, since there's no {memory} RAM"
+            ));
+        }
+        if self.has_ecc(memory) {
+            return Err(format!(
+                "{what} cannot be placed in {memory}, since the runtime zeroes its ECC RAM on every boot"
+            ));
+        }
+        Ok(())
+    }
 
+    /// Make sure that the boot ROM can load an image into the first `bytes` of `memory`.
+    ///
+    /// The boot ROM loads the image before the runtime configures FlexRAM and
+    /// zeroes ECC RAM.
+    fn check_rom_image(&self, what: &str, memory: Memory, bytes: usize) -> Result<(), String> {
+        if self.family == Family::Imxrt1180 {
+            return Err(format!("{what} isn't supported on the 1180"));
+        }
+        let Some(region) = rom_image_region(
+            self.family,
+            memory,
+            &self.flexram_layout,
+            self.flexram_ecc,
+            self.mecc64,
+        ) else {
+            return Err(format!(
+                "{what} can only be placed in ITCM or OCRAM, not {memory}"
+            ));
+        };
+        self.check_retained(what, memory)?;
+        if self.flexram_layout != self.family.default_flexram_layout() {
+            return Err(format!(
+                "{what} requires the default FlexRAM layout, since the boot ROM loads the image before the runtime configures FlexRAM"
+            ));
+        }
+        if bytes >= region.size {
+            return Err(format!("{what} must be smaller than {memory}"));
+        }
         Ok(())
     }
 
@@ -878,6 +2803,34 @@ impl RuntimeBuilder {
         prevent_flash!(stack)?;
         prevent_flash!(heap)?;
 
+        if !self.itcm_functions.is_empty()
+            && layout_count_of(FlexRamKind::Itcm, &self.flexram_layout) == 0
+        {
+            return Err(String::from(
+                "Functions cannot be placed in ITCM without any ITCM banks",
+            ));
+        }
+        if let Some(pattern) = self.itcm_functions.iter().find(|pattern| {
+            pattern.is_empty()
+                || pattern
+                    .chars()
+                    .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"'))
+        }) {
+            return Err(format!(
+                "ITCM function pattern '{pattern}' must be non-empty, and it cannot contain whitespace, parentheses, or quotes"
+            ));
+        }
+
+        for heap in &self.named_heaps {
+            if !heap.has_valid_name() {
+                return Err(format!(
+                    "Heap name '{}' must be an ASCII identifier",
+                    heap.name
+                ));
+            }
+            prevent_flash(&format!("heap.{}", heap.name), heap.memory)?;
+        }
+
         if self.flexram_ecc.is_enable() && !self.family.supports_flexram_ecc() {
             return Err(format!(
                 "{:?} doesn't support FlexRAM-managed ECC",
@@ -888,28 +2841,345 @@ impl RuntimeBuilder {
             return Err(format!("{:?} doesn't support MECC64", self.family));
         }
 
-        Ok(())
+        if let Some(limit) = self.text_spill {
+            if self.flash_opts.is_none() || self.text != Memory::Itcm {
+                return Err(String::from(
+                    "Text spill-over requires a flash image with .text placed in ITCM",
+                ));
+            }
+            let itcm_count = layout_count_of(FlexRamKind::Itcm, &self.flexram_layout);
+            let itcm = self.family.itcm_start_size(itcm_count);
+            if limit == 0 || limit > itcm.size {
+                return Err(format!(
+                    "ITCM limit {limit} for .text must be non-zero and at most the ITCM size, {}",
+                    itcm.size
+                ));
+            }
+        }
+
+        if let Some(memory) = self.unwind_tables
+            && !self.memories().contains(&memory)
+        {
+            return Err(format!(
+                "Unwind tables cannot be placed in {memory}, since there's no {memory}"
+            ));
+        }
+        if let Some(tables) = self.unwind_tables {
+            // The index table's PREL31 offsets reach +/- 1 GiB.
+            let reaches = |table: Memory, code: Memory| {
+                self.memory_origin(table).abs_diff(self.memory_origin(code)) < 1 << 30
+            };
+            let memories = self.memories();
+            let mut code = vec![(String::from(".text"), self.text)];
+            if self.text_spill.is_some() {
+                code.push((String::from("spilled .text"), Memory::Flash));
+            }
+            if !self.itcm_functions.is_empty() {
+                code.push((String::from("the ITCM functions"), Memory::Itcm));
+            }
+            code.extend(
+                self.crate_code
+                    .iter()
+                    .map(|(name, memory)| (format!("the code of crate '{name}'"), *memory)),
+            );
+            code.retain(|(_, memory)| memories.contains(memory));
+            if let Some((what, memory)) = code.iter().find(|(_, memory)| !reaches(tables, *memory))
+            {
+                let suggestion = memories
+                    .iter()
+                    .find(|table| code.iter().all(|(_, memory)| reaches(**table, *memory)))
+                    .map_or_else(
+                        || String::from("Place the code within 1 GiB of the unwind tables"),
+                        |table| format!("Place the unwind tables in {table}"),
+                    );
+                return Err(format!(
+                    "Unwind tables in {tables} can't reach {what} in {memory}. {suggestion}"
+                ));
+            }
+        }
+
+        if self.reset_reason && self.family == Family::Imxrt1180 {
+            return Err(String::from(
+                "Reset reasons aren't supported on the 1180 yet",
+            ));
+        }
+
+        if let Some((memory, bytes)) = self.crash_log {
+            // Pre-init zeroes a crash log in ECC RAM after a power-on reset. The
+            // status that indicates the reset sticks until the runtime clears it.
+            if !self.has_ecc(memory) {
+                self.check_retained("The crash log", memory)?;
+            } else if !self.reset_reason {
+                return Err(format!(
+                    "The crash log can only be placed in {memory}, which is protected by ECC, if the runtime captures the reset reason. Use RuntimeBuilder::reset_reason"
+                ));
+            }
+            if bytes <= CRASH_LOG_HEADER_SIZE {
+                return Err(format!(
+                    "The crash log must be larger than its {CRASH_LOG_HEADER_SIZE} byte header"
+                ));
+            }
+        }
+
+        if let Some((memory, _)) = self.fault_handler {
+            self.check_retained("The fault record", memory)?;
+        }
+
+        if self.position_independent {
+            if self
+                .flash_opts
+                .as_ref()
+                .is_none_or(|flash_opts| flash_opts.boot_header)
+            {
+                return Err(String::from(
+                    "Position-independent images must be built with RuntimeBuilder::in_flash",
+                ));
+            }
+            for (name, memory) in [
+                ("The vector table", Some(self.vectors)),
+                (".text", Some(self.text)),
+                (".rodata", Some(self.rodata)),
+                (".data", Some(self.data)),
+                ("The unwind tables", self.unwind_tables),
+            ] {
+                if memory == Some(Memory::Flash) {
+                    return Err(format!(
+                        "{name} cannot be placed in flash in a position-independent image"
+                    ));
+                }
+            }
+            if self.text_spill.is_some() {
+                return Err(String::from(
+                    ".text cannot spill into flash in a position-independent image",
+                ));
+            }
+            if self.image_check.is_some() {
+                return Err(String::from(
+                    "A position-independent image cannot check its image",
+                ));
+            }
+        }
+
+        if let Some(flash_opts) = &self.flash_opts
+            && let Some((memory, bytes)) = flash_opts.rom_copy
+        {
+            if !flash_opts.boot_header {
+                return Err(String::from(
+                    "The boot ROM can only copy an image that has a boot header",
+                ));
+            }
+            self.check_rom_image("The boot ROM's copy", memory, bytes)?;
+            if bytes <= 0x2000 || bytes > flash_opts.size {
+                return Err(String::from(
+                    "The boot ROM's copy must be larger than the 8 KiB boot header, and no larger than flash",
+                ));
+            }
+            if self.text_spill.is_some()
+                || !self.compressed.is_empty()
+                || self.image_check.is_some()
+            {
+                return Err(String::from(
+                    "An image that's copied by the boot ROM cannot spill .text, compress its load images, or check its image",
+                ));
+            }
+        }
+
+        if let Some((memory, bytes)) = self.serial_download {
+            if self.flash_opts.is_some() {
+                return Err(String::from(
+                    "Serial download images must be built with RuntimeBuilder::from_ram",
+                ));
+            }
+            self.check_rom_image("The serial download image", memory, bytes)?;
+            if bytes <= 0x1000 {
+                return Err(String::from(
+                    "The serial download image must be larger than its 4 KiB boot header",
+                ));
+            }
+        }
+
+        if self.image_check.is_some() && self.flash_opts.is_none() {
+            return Err(String::from("Image checks require a flash image"));
+        }
+
+        for section in &self.compressed {
+            let memory = match section {
+                Section::Text => self.text,
+                Section::Rodata => self.rodata,
+                Section::Data => self.data,
+                Section::Bss => {
+                    return Err(String::from(".bss has no load image to compress"));
+                }
+            };
+            if self.flash_opts.is_none() || memory == Memory::Flash {
+                return Err(format!(
+                    "{section} can only be compressed in a flash image, when it's placed in RAM"
+                ));
+            }
+        }
+
+        let memories = self.memories();
+        for (name, memory) in &self.crate_code {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("Crate name '{name}' must be an ASCII identifier"));
+            }
+            if *memory != Memory::Flash && !memories.contains(memory) {
+                return Err(format!(
+                    "Code from crate '{name}' cannot be placed in {memory}, since there's no {memory}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes where the linker script places an output section.
+struct Placement {
+    /// The name of the output section.
+    section: String,
+    /// The memory holding the section.
+    memory: Memory,
+    /// The `RuntimeBuilder` method that changes the placement.
+    method: Option<&'static str>,
+    /// Is there a load image in flash?
+    loaded: bool,
+}
+
+impl Placement {
+    fn new(
+        section: impl Into<String>,
+        memory: Memory,
+        method: Option<&'static str>,
+        loaded: bool,
+    ) -> Self {
+        Self {
+            section: section.into(),
+            memory,
+            method,
+            loaded,
+        }
+    }
+    /// Suggest how to use the builder method to move this section.
+    fn suggestion(&self) -> Option<String> {
+        let method = self.method?;
+        let memory = match (method, self.memory) {
+            ("text" | "rodata" | "unwind_tables", _) => Memory::Flash,
+            (_, Memory::Ocram) => Memory::Dtcm,
+            (_, Memory::Flash | Memory::Itcm | Memory::Dtcm) => Memory::Ocram,
+        };
+        Some(match method {
+            "named_heap" | "crash_log" | "fault_handler" => format!("RuntimeBuilder::{method}"),
+            _ => format!("RuntimeBuilder::{method}(Memory::{memory:?})"),
+        })
+    }
+}
+
+/// The lookup table for a CRC-32 with the reflected polynomial 0xEDB88320.
+fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    for (index, entry) in table.iter_mut().enumerate() {
+        *entry = (0..8).fold(index as u32, |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        });
+    }
+    table
+}
+
+/// Select the placements that occupy flash.
+fn flash_placements(placements: &[Placement]) -> Vec<&Placement> {
+    placements
+        .iter()
+        .filter(|p| p.memory == Memory::Flash || p.loaded)
+        .collect()
+}
+
+/// Produce an expression for the highest end address of all placements.
+///
+/// `end` is either `ADDR` or `LOADADDR`. Returns `None` if there are no
+/// placements.
+fn end_of(placements: &[&Placement], end: &str) -> Option<String> {
+    let (last, rest) = placements.split_last()?;
+    let mut used = format!("{end}({0}) + SIZEOF({0})", last.section);
+    for placement in rest.iter().rev() {
+        used = format!("MAX({end}({0}) + SIZEOF({0}), {used})", placement.section);
+    }
+    Some(used)
+}
+
+/// Generate the stack section.
+///
+/// A fixed-size stack is placed at the start of its region. A stack
+/// that consumes the remainder of its region ends at the end of its region.
+fn write_stack(output: &mut dyn Write, stack_size: RegionSize) -> io::Result<()> {
+    writeln!(output, "  .stack (NOLOAD) : ALIGN(8)")?;
+    writeln!(output, "  {{")?;
+    writeln!(output, "    __estack = .;")?;
+    match stack_size {
+        RegionSize::Fixed(_) => writeln!(output, "    . += ALIGN(__stack_size, 8);")?,
+        RegionSize::Remainder(_) => writeln!(
+            output,
+            "    . = ORIGIN(REGION_STACK) + LENGTH(REGION_STACK);"
+        )?,
     }
+    writeln!(output, "    __sstack = .;")?;
+    writeln!(output, "    /* Symbol expected by cortex-m-rt */")?;
+    writeln!(output, "    _stack_start = __sstack;")?;
+    writeln!(output, "  }} > REGION_STACK")?;
+    Ok(())
 }
 
 /// Write RAM-like memory blocks.
 ///
 /// Skips a section if there's no FlexRAM block allocated. If a user references one
-/// of this skipped sections, linking fails.
+/// of this skipped sections, linking fails. `rom_image` describes the RAM that holds
+/// an image loaded by the boot ROM, if there is one; it's excluded from its memory.
 fn write_flexram_memories(
     output: &mut dyn Write,
     family: Family,
     flexram_layout: &[FlexRamKind],
     flexram_ecc: FlexRamEcc,
     mecc64: Mecc64,
+    itcm_text_limit: Option<usize>,
+    rom_image: Option<(Memory, usize)>,
 ) -> io::Result<()> {
     let itcm_count = layout_count_of(FlexRamKind::Itcm, flexram_layout);
     let dtcm_count = layout_count_of(FlexRamKind::Dtcm, flexram_layout);
     let ocram_count = layout_count_of(FlexRamKind::Ocram, flexram_layout);
 
+    // The image is taken from the start of its memory.
+    let after_rom_image = |memory: Memory, region: MemoryRegion| match rom_image {
+        Some((image, bytes)) if image == memory => MemoryRegion {
+            start: region.start + bytes,
+            size: region.size - bytes,
+            ..region
+        },
+        _ => region,
+    };
+
     if itcm_count > 0 {
         let itcm = family.itcm_start_size(itcm_count);
-        writeln!(output, "ITCM {itcm}")?;
+        if let Some(limit) = itcm_text_limit {
+            // .text is limited to the start of ITCM. Everything else
+            // is placed behind it.
+            let text = MemoryRegion {
+                size: limit,
+                ..itcm
+            };
+            let rest = MemoryRegion {
+                start: itcm.start + limit,
+                size: itcm.size - limit,
+                ..itcm
+            };
+            writeln!(output, "ITCM_TEXT {text}")?;
+            writeln!(output, "ITCM {rest}")?;
+        } else {
+            writeln!(output, "ITCM {}", after_rom_image(Memory::Itcm, itcm))?;
+        }
     }
     if dtcm_count > 0 {
         writeln!(
@@ -921,18 +3191,23 @@ fn write_flexram_memories(
 
     let ocram = family.ocram_start_size(ocram_count, flexram_ecc, mecc64);
     if ocram.size > 0 {
-        writeln!(output, "OCRAM {ocram}",)?;
+        writeln!(output, "OCRAM {}", after_rom_image(Memory::Ocram, ocram))?;
     }
     Ok(())
 }
 
 /// Generate spans for zeroing ECC RAM.
+///
+/// If the crash log is in ECC RAM, its memory's span ends at the crash log, and
+/// the tail span continues behind it. Pre-init only zeroes the crash log's span
+/// after a power-on reset.
 fn write_ecc_zero_spans(
     output: &mut dyn Write,
     family: Family,
     flexram_layout: &[FlexRamKind],
     flexram_ecc: FlexRamEcc,
     mecc64: Mecc64,
+    crash_log: Option<Memory>,
 ) -> io::Result<()> {
     let itcm_count = layout_count_of(FlexRamKind::Itcm, flexram_layout);
     let dtcm_count = layout_count_of(FlexRamKind::Dtcm, flexram_layout);
@@ -941,6 +3216,7 @@ fn write_ecc_zero_spans(
     // If memory isn't available or doesn't need
     // use ECC, then we don't need to zero it.
     // Generate dummy values that skip the loops.
+    let mut spans = Vec::new();
 
     if itcm_count > 0 && flexram_ecc.is_enable() {
         // Make sure to zero the reserved regions, too,
@@ -948,19 +3224,23 @@ fn write_ecc_zero_spans(
         let itcm = family.itcm_start_size(itcm_count);
         let start = itcm.start - 32;
         let end = start + itcm.size + 32;
-        writeln!(output, "__sitcm = {:#010X};", start)?;
-        writeln!(output, "__eitcm = {:#010X};", end)?;
+        spans.push((
+            Memory::Itcm,
+            format!("{start:#010X}"),
+            format!("{end:#010X}"),
+        ));
     } else {
-        writeln!(output, "__sitcm = 0;")?;
-        writeln!(output, "__eitcm = 0;")?;
+        spans.push((Memory::Itcm, String::from("0"), String::from("0")));
     };
 
     if dtcm_count > 0 && flexram_ecc.is_enable() {
-        writeln!(output, "__sdtcm = ORIGIN(DTCM);")?;
-        writeln!(output, "__edtcm = ORIGIN(DTCM) + LENGTH(DTCM);")?;
+        spans.push((
+            Memory::Dtcm,
+            String::from("ORIGIN(DTCM)"),
+            String::from("ORIGIN(DTCM) + LENGTH(DTCM)"),
+        ));
     } else {
-        writeln!(output, "__sdtcm = 0;")?;
-        writeln!(output, "__edtcm = 0;")?;
+        spans.push((Memory::Dtcm, String::from("0"), String::from("0")));
     };
 
     let ocram = family.ocram_start_size(ocram_count, flexram_ecc, mecc64);
@@ -969,16 +3249,62 @@ fn write_ecc_zero_spans(
     // But we're not prepared to do that, so incur the loop for the whole
     // region no matter which is enabled.
     if ocram.size > 0 && (mecc64.is_enable() || flexram_ecc.is_enable()) {
-        writeln!(output, "__socram = {:#010X};", ocram.start)?;
-        writeln!(output, "__eocram = {:#010X};", ocram.start + ocram.size)?;
+        spans.push((
+            Memory::Ocram,
+            format!("{:#010X}", ocram.start),
+            format!("{:#010X}", ocram.start + ocram.size),
+        ));
+    } else {
+        spans.push((Memory::Ocram, String::from("0"), String::from("0")));
+    }
+
+    let mut tail = (String::from("0"), String::from("0"));
+    for (memory, start, end) in spans {
+        let name = memory.to_string().to_lowercase();
+        writeln!(output, "__s{name} = {start};")?;
+        if crash_log == Some(memory) {
+            writeln!(output, "__e{name} = __scrashlog;")?;
+            tail = (String::from("__ecrashlog"), end);
+        } else {
+            writeln!(output, "__e{name} = {end};")?;
+        }
+    }
+    writeln!(output, "__secc_tail = {};", tail.0)?;
+    writeln!(output, "__eecc_tail = {};", tail.1)?;
+    if crash_log.is_some() {
+        writeln!(output, "__secc_crashlog = __scrashlog;")?;
+        writeln!(output, "__eecc_crashlog = __ecrashlog;")?;
     } else {
-        writeln!(output, "__socram = 0;")?;
-        writeln!(output, "__eocram = 0;")?;
+        writeln!(output, "__secc_crashlog = 0;")?;
+        writeln!(output, "__eecc_crashlog = 0;")?;
     }
 
     Ok(())
 }
 
+/// Returns the region of a memory that can hold an image that's loaded by the boot ROM.
+///
+/// Returns `None` if the boot ROM can't load the image into the memory.
+fn rom_image_region(
+    family: Family,
+    memory: Memory,
+    flexram_layout: &[FlexRamKind],
+    flexram_ecc: FlexRamEcc,
+    mecc64: Mecc64,
+) -> Option<MemoryRegion> {
+    match memory {
+        Memory::Itcm => {
+            Some(family.itcm_start_size(layout_count_of(FlexRamKind::Itcm, flexram_layout)))
+        }
+        Memory::Ocram => Some(family.ocram_start_size(
+            layout_count_of(FlexRamKind::Ocram, flexram_layout),
+            flexram_ecc,
+            mecc64,
+        )),
+        Memory::Dtcm | Memory::Flash => None,
+    }
+}
+
 /// Generate a linker script MEMORY command that includes a FLASH block.
 fn write_flash_memory_map(
     output: &mut dyn Write,
@@ -987,6 +3313,7 @@ fn write_flash_memory_map(
     flexram_layout: &[FlexRamKind],
     flexram_ecc: FlexRamEcc,
     mecc64: Mecc64,
+    itcm_text_limit: Option<usize>,
 ) -> io::Result<()> {
     writeln!(
         output,
@@ -994,16 +3321,37 @@ fn write_flash_memory_map(
         family, flash_opts.size
     )?;
     writeln!(output, "MEMORY {{")?;
-    writeln!(
+    if let Some((memory, bytes)) = flash_opts.rom_copy {
+        // The image is linked to run from the boot ROM's copy.
+        let region = rom_image_region(family, memory, flexram_layout, flexram_ecc, mecc64)
+            .expect("Already checked");
+        writeln!(
+            output,
+            "FLASH {}",
+            MemoryRegion {
+                size: bytes,
+                ..region
+            }
+        )?;
+    } else {
+        writeln!(
+            output,
+            "FLASH (RX) : ORIGIN = {:#X}, LENGTH = {:#X}",
+            flash_opts.flash_origin(family).expect("Already checked"),
+            flash_opts.size
+        )?;
+    }
+    write_flexram_memories(
         output,
-        "FLASH (RX) : ORIGIN = {:#X}, LENGTH = {:#X}",
-        flash_opts.flash_origin(family).expect("Already checked"),
-        flash_opts.size
+        family,
+        flexram_layout,
+        flexram_ecc,
+        mecc64,
+        itcm_text_limit,
+        flash_opts.rom_copy,
     )?;
-    write_flexram_memories(output, family, flexram_layout, flexram_ecc, mecc64)?;
     writeln!(output, "}}")?;
     writeln!(output, "__fcb_offset = {:#X};", family.fcb_offset())?;
-    write_ecc_zero_spans(output, family, flexram_layout, flexram_ecc, mecc64)?;
     Ok(())
 }
 
@@ -1017,15 +3365,36 @@ fn write_ram_memory_map(
     flexram_layout: &[FlexRamKind],
     flexram_ecc: FlexRamEcc,
     mecc64: Mecc64,
+    serial_download: Option<(Memory, usize)>,
 ) -> io::Result<()> {
     writeln!(
         output,
         "/* Memory map for '{family:?}' that executes from RAM. */",
     )?;
     writeln!(output, "MEMORY {{")?;
-    write_flexram_memories(output, family, flexram_layout, flexram_ecc, mecc64)?;
+    if let Some((memory, bytes)) = serial_download {
+        // The image that's loaded by the serial downloader.
+        let region = rom_image_region(family, memory, flexram_layout, flexram_ecc, mecc64)
+            .expect("Already checked");
+        writeln!(
+            output,
+            "FLASH {}",
+            MemoryRegion {
+                size: bytes,
+                ..region
+            }
+        )?;
+    }
+    write_flexram_memories(
+        output,
+        family,
+        flexram_layout,
+        flexram_ecc,
+        mecc64,
+        None,
+        serial_download,
+    )?;
     writeln!(output, "}}")?;
-    write_ecc_zero_spans(output, family, flexram_layout, flexram_ecc, mecc64)?;
     Ok(())
 }
 
@@ -1070,6 +3439,23 @@ impl Family {
             Family::Imxrt1180 => 0x1180,
         }
     }
+    /// Find the family for an identifier produced by [`id`](Self::id).
+    #[cfg(feature = "report")]
+    const fn from_id(id: u32) -> Option<Self> {
+        Some(match id {
+            0x1010 => Family::Imxrt1010,
+            0x1015 => Family::Imxrt1015,
+            0x1020 => Family::Imxrt1020,
+            0x1040 => Family::Imxrt1040,
+            0x1050 => Family::Imxrt1050,
+            0x1060 => Family::Imxrt1060,
+            0x1064 => Family::Imxrt1064,
+            0x1160 => Family::Imxrt1160,
+            0x1170 => Family::Imxrt1170,
+            0x1180 => Family::Imxrt1180,
+            _ => return None,
+        })
+    }
     /// How many FlexRAM banks are available?
     pub const fn flexram_bank_count(self) -> usize {
         match self {
@@ -1436,6 +3822,103 @@ impl Family {
     pub fn flexspi_start_addr(self, flexspi: FlexSpi) -> Option<u32> {
         flexspi.start_address(self)
     }
+
+    /// Returns the fewest FlexRAM banks that satisfy the RAM requirements.
+    ///
+    /// The banks account for the OCRAM banks needed by the boot ROM, and the
+    /// ITCM reservation for null pointers. OCRAM requirements are first
+    /// satisfied with any dedicated OCRAM, considering the ECC configuration.
+    /// Use [`RuntimeBuilder::fit_flexram_banks`] to let the runtime builder
+    /// select the banks.
+    ///
+    /// ```
+    /// use imxrt_rt::{Family, FlexRamBanks, FlexRamEcc, Mecc64, RamRequirements};
+    ///
+    /// let banks = Family::Imxrt1010.fit_flexram_banks(
+    ///     RamRequirements {
+    ///         itcm: 40 * 1024,
+    ///         dtcm: 16 * 1024,
+    ///         ocram: 0,
+    ///     },
+    ///     FlexRamEcc::Disable,
+    ///     Mecc64::Disable,
+    /// ).unwrap();
+    /// assert_eq!(banks, FlexRamBanks { ocram: 1, itcm: 2, dtcm: 1 });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chip doesn't have enough FlexRAM banks to
+    /// satisfy the requirements, or if the chip doesn't support the ECC
+    /// configuration.
+    pub fn fit_flexram_banks(
+        self,
+        requirements: RamRequirements,
+        flexram_ecc: FlexRamEcc,
+        mecc64: Mecc64,
+    ) -> Result<FlexRamBanks, Box<dyn std::error::Error>> {
+        if flexram_ecc.is_enable() && !self.supports_flexram_ecc() {
+            return Err(format!("{self:?} doesn't support FlexRAM-managed ECC").into());
+        }
+        if mecc64.is_enable() && !self.supports_mecc64() {
+            return Err(format!("{self:?} doesn't support MECC64").into());
+        }
+
+        let bank_count = self.flexram_bank_count();
+        let fewest = |fits: &dyn Fn(usize) -> bool| (0..=bank_count).find(|banks| fits(*banks));
+        let itcm = fewest(&|banks| self.itcm_start_size(banks).size >= requirements.itcm);
+        let dtcm = fewest(&|banks| banks * self.flexram_bank_size() >= requirements.dtcm);
+        let ocram = fewest(&|banks| {
+            banks >= self.bootrom_ocram_banks()
+                && self.ocram_start_size(banks, flexram_ecc, mecc64).size >= requirements.ocram
+        });
+
+        let banks = match (itcm, dtcm, ocram) {
+            (Some(itcm), Some(dtcm), Some(ocram)) if itcm + dtcm + ocram <= bank_count => {
+                FlexRamBanks { ocram, itcm, dtcm }
+            }
+            _ => {
+                return Err(format!(
+                    "Chip {self:?} doesn't have enough FlexRAM banks for {requirements:?}"
+                )
+                .into());
+            }
+        };
+
+        if self == Family::Imxrt1180 {
+            // Only a few configurations are supported.
+            return [(1, 1), (2, 0), (0, 2)]
+                .into_iter()
+                .find(|(itcm, dtcm)| banks.ocram == 0 && banks.itcm <= *itcm && banks.dtcm <= *dtcm)
+                .map(|(itcm, dtcm)| FlexRamBanks {
+                    ocram: 0,
+                    itcm,
+                    dtcm,
+                })
+                .ok_or_else(|| {
+                    format!("Chip {self:?} has no FlexRAM configuration for {requirements:?}")
+                        .into()
+                });
+        }
+
+        Ok(banks)
+    }
+}
+
+/// RAM requirements, in bytes, for each kind of RAM.
+///
+/// Use with [`Family::fit_flexram_banks`] or [`RuntimeBuilder::fit_flexram_banks`]
+/// to find the fewest FlexRAM banks that hold your program.
+///
+/// The OCRAM requirement includes any dedicated OCRAM on your chip.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RamRequirements {
+    /// Bytes needed in ITCM.
+    pub itcm: usize,
+    /// Bytes needed in DTCM.
+    pub dtcm: usize,
+    /// Bytes needed in OCRAM.
+    pub ocram: usize,
 }
 
 /// FlexRAM bank allocations.
@@ -1450,6 +3933,9 @@ impl Family {
 ///
 /// Banks are typically 32KiB large.
 ///
+/// To find the fewest banks that hold your program, see
+/// [`Family::fit_flexram_banks`].
+///
 /// If you need to control the _layout_, or assignment, of FlexRAM
 /// banks, you should define your own collection of [`FlexRamKind`]
 /// and use [`flexram_layout`](RuntimeBuilder::flexram_layout) to
@@ -1585,9 +4071,9 @@ impl std::fmt::Display for MemoryRegion {
 
 #[cfg(test)]
 mod tests {
-    use crate::{FlexRamEcc, Mecc64, Memory};
+    use crate::{FlexRamEcc, Mecc64, Memory, RamRequirements, Section};
 
-    use super::{Family, FlexRamBanks, RuntimeBuilder};
+    use super::{Family, FaultAction, FlexRamBanks, ImageCheckFailure, RuntimeBuilder};
     use std::{error, io};
 
     const MOST_FAMILIES: &[Family] = &[
@@ -1757,6 +4243,839 @@ mod tests {
         }
     }
 
+    #[test]
+    fn runtime_builder_named_heaps() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .named_heap("dsp", Memory::Dtcm, 1024)
+            .named_heap("net", Memory::Dtcm, 0)
+            .named_heap("net", Memory::Ocram, 4096)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+
+        assert!(script.contains("__sheap_dsp = .;"));
+        assert!(script.contains("__eheap_dsp = .;"));
+        assert!(script.contains(". += ALIGN(0x00000400, 4);"));
+        assert!(script.contains("} > DTCM"));
+
+        // Replaces the previous allocation.
+        assert_eq!(script.matches("__sheap_net = .;").count(), 1);
+        assert!(script.contains(". += ALIGN(0x00001000, 4);"));
+        assert!(script.contains("} > OCRAM"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_remainder_sizes() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .stack_size_remainder(1024)
+            .heap_size_remainder(2048)
+            .heap(Memory::Ocram)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+
+        assert!(script.contains("__stack_size = 0x00000400;"));
+        assert!(script.contains("__heap_size = 0x00000800;"));
+        assert!(script.contains("__heap_remainder = 1;"));
+        assert_eq!(script.matches(".stack (NOLOAD)").count(), 1);
+        let stack = script.find(".stack (NOLOAD)").unwrap();
+        let heap = script.find(".heap (NOLOAD)").unwrap();
+        assert!(heap < stack, "Stack should be behind the heap");
+
+        // A fixed size overrides the remainder.
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .stack_size_remainder(1024)
+            .stack_size(4096)
+            .heap_size_remainder(2048)
+            .heap_size(0)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+
+        assert!(script.contains("__stack_size = 0x00001000;"));
+        assert!(script.contains("__heap_remainder = 0;"));
+        let stack = script.find(".stack (NOLOAD)").unwrap();
+        let heap = script.find(".heap (NOLOAD)").unwrap();
+        assert!(stack < heap, "Stack should be in front of the heap");
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_remainder_conflicts() {
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .stack(Memory::Dtcm)
+            .stack_size_remainder(0)
+            .heap(Memory::Dtcm)
+            .heap_size_remainder(0)
+            .write_linker_script(&mut io::sink());
+        assert!(res.is_err());
+
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .heap(Memory::Ocram)
+            .heap_size_remainder(0)
+            .named_heap("dsp", Memory::Ocram, 1024)
+            .write_linker_script(&mut io::sink());
+        assert!(res.is_err());
+
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .heap(Memory::Ocram)
+            .heap_size_remainder(0)
+            .named_heap("dsp", Memory::Dtcm, 1024)
+            .write_linker_script(&mut io::sink())
+            .unwrap();
+    }
+
+    #[test]
+    fn runtime_builder_region_checks() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 0,
+                itcm: 4,
+                dtcm: 12,
+            })
+            .rodata(Memory::Dtcm)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+
+        assert!(script.contains("ERROR(imxrt-rt): FLASH overflows."));
+        assert!(script.contains("ERROR(imxrt-rt): ITCM overflows."));
+        assert!(script.contains("ERROR(imxrt-rt): DTCM overflows."));
+        assert!(script.contains("ERROR(imxrt-rt): OCRAM overflows."));
+        assert!(script.contains("ITCM holds .itcm, .text."));
+        assert!(
+            script.contains("- Move .text out of ITCM with RuntimeBuilder::text(Memory::Flash).")
+        );
+        assert!(script.contains("DTCM holds .stack, .vector_table, .dtcm, .rodata, .heap."));
+
+        // No flash in a RAM image.
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1010)
+            .flexram_banks(FlexRamBanks {
+                ocram: 1,
+                itcm: 1,
+                dtcm: 2,
+            })
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("ERROR(imxrt-rt): FLASH overflows."));
+        assert!(script.contains("ITCM holds .xip, .itcm, .copy_table, .text."));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_budgets() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .budget(Section::Text, 1024)
+            .budget(Section::Text, 2048)
+            .budget(Section::Bss, 512)
+            .image_budget(8 * 1024)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("ASSERT(SIZEOF(.text) <= 0x400"));
+        assert!(script.contains("ASSERT(SIZEOF(.text) <= 0x800"));
+        assert!(script.contains("ASSERT(SIZEOF(.bss) <= 0x200"));
+        assert!(script.contains("The flash image exceeds its budget of 8192 bytes."));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1060)
+            .image_budget(8 * 1024)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("The flash image exceeds its budget"));
+        Ok(())
+    }
+
+    #[test]
+    fn fit_flexram_banks() {
+        let fit = |family: Family, itcm, dtcm, ocram| {
+            family
+                .fit_flexram_banks(
+                    RamRequirements { itcm, dtcm, ocram },
+                    FlexRamEcc::Disable,
+                    Mecc64::Disable,
+                )
+                .ok()
+        };
+        let banks = |ocram, itcm, dtcm| Some(FlexRamBanks { ocram, itcm, dtcm });
+
+        // Boot ROM needs an OCRAM bank.
+        assert_eq!(fit(Family::Imxrt1010, 0, 0, 0), banks(1, 0, 0));
+        // ITCM reserves 32 bytes for null pointers.
+        assert_eq!(fit(Family::Imxrt1010, 32 * 1024 - 32, 0, 0), banks(1, 1, 0));
+        assert_eq!(fit(Family::Imxrt1010, 32 * 1024 - 31, 0, 0), banks(1, 2, 0));
+        assert_eq!(
+            fit(Family::Imxrt1010, 0, 32 * 1024 + 1, 32 * 1024 + 1),
+            banks(2, 0, 2)
+        );
+        assert_eq!(fit(Family::Imxrt1010, 1, 1, 64 * 1024 + 1), None);
+
+        // Dedicated OCRAM comes first.
+        assert_eq!(fit(Family::Imxrt1060, 0, 0, 512 * 1024), banks(0, 0, 0));
+        assert_eq!(fit(Family::Imxrt1060, 0, 0, 512 * 1024 + 1), banks(1, 0, 0));
+        assert_eq!(fit(Family::Imxrt1060, 512 * 1024, 0, 0), None);
+
+        // FlexRAM OCRAM isn't contiguous with dedicated OCRAM when ECC is enabled.
+        let ocram = Family::Imxrt1170
+            .ocram_start_size(0, FlexRamEcc::Enable, Mecc64::Disable)
+            .size;
+        let requirements = RamRequirements {
+            ocram: ocram + 1,
+            ..Default::default()
+        };
+        assert!(
+            Family::Imxrt1170
+                .fit_flexram_banks(requirements, FlexRamEcc::Enable, Mecc64::Disable)
+                .is_err()
+        );
+        assert!(
+            Family::Imxrt1170
+                .fit_flexram_banks(requirements, FlexRamEcc::Disable, Mecc64::Disable)
+                .is_ok()
+        );
+
+        // Only a few 1180 configurations.
+        assert_eq!(fit(Family::Imxrt1180, 0, 0, 0), banks(0, 1, 1));
+        assert_eq!(fit(Family::Imxrt1180, 128 * 1024 + 1, 0, 0), banks(0, 2, 0));
+        assert_eq!(fit(Family::Imxrt1180, 0, 128 * 1024 + 1, 0), banks(0, 0, 2));
+        assert_eq!(fit(Family::Imxrt1180, 128 * 1024 + 1, 1, 0), None);
+    }
+
+    #[test]
+    fn runtime_builder_fit_flexram_banks() -> Result<(), Error> {
+        let requirements = RamRequirements {
+            itcm: 64 * 1024,
+            dtcm: 96 * 1024,
+            ocram: 0,
+        };
+
+        let mut fit = Vec::new();
+        let mut builder = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024);
+        builder.fit_flexram_banks(requirements);
+        builder.write_linker_script(&mut fit)?;
+
+        let mut expected = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 0,
+                itcm: 3,
+                dtcm: 3,
+            })
+            .write_linker_script(&mut expected)?;
+        assert_eq!(
+            String::from_utf8(fit)?,
+            String::from_utf8(expected.clone())?
+        );
+
+        // Explicit banks override the fit.
+        let mut banks = Vec::new();
+        builder
+            .flexram_banks(Family::Imxrt1060.default_flexram_banks())
+            .write_linker_script(&mut banks)?;
+        assert_ne!(banks, expected);
+
+        let mut script = Vec::new();
+        let result = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .fit_flexram_banks(RamRequirements {
+                itcm: 1024 * 1024,
+                ..requirements
+            })
+            .write_linker_script(&mut script);
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_itcm_functions() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .text(Memory::Flash)
+            .itcm_functions(&["*hot_loop*"])
+            .itcm_functions(&["memcpy"])
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("    *(.text.*hot_loop*)\n    *(.text.memcpy)\n"));
+        assert!(script.contains("  } > ITCM AT> FLASH\n"));
+        assert!(script.contains("    LONG(LOADADDR(.itcm));\n"));
+
+        let mut script = Vec::new();
+        let result = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 8,
+                itcm: 0,
+                dtcm: 8,
+            })
+            .itcm_functions(&["*hot_loop*"])
+            .write_linker_script(&mut script);
+        assert!(result.is_err());
+
+        for pattern in ["", "a b", "a)"] {
+            let result = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .itcm_functions(&[pattern])
+                .write_linker_script(&mut script);
+            assert!(result.is_err(), "{pattern}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_crate_code() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .text(Memory::Ocram)
+            .crate_code("dsp-filters", Memory::Dtcm)
+            .crate_code("dsp_filters", Memory::Itcm)
+            .crate_code("micromath", Memory::Flash)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains(
+            "    *(.imxrt_rt.itcm .imxrt_rt.itcm.*)\n    *libdsp_filters-*.rlib:*(.text .text.*)\n"
+        ));
+        assert!(!script.contains(
+            "    *(.imxrt_rt.dtcm .imxrt_rt.dtcm.*)\n    *libdsp_filters-*.rlib:*(.text .text.*)\n"
+        ));
+        assert!(script.contains(
+            "    *libmicromath-*.rlib:*(.text .text.*)\n    __imxrt_rt_exip_items = .;\n"
+        ));
+
+        for name in ["", "dsp.filters", "dsp filters"] {
+            let result = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .crate_code(name, Memory::Itcm)
+                .write_linker_script(&mut io::sink());
+            assert!(result.is_err(), "{name}");
+        }
+
+        let result = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 8,
+                itcm: 0,
+                dtcm: 8,
+            })
+            .crate_code("micromath", Memory::Itcm)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_text_spill() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 8,
+                itcm: 4,
+                dtcm: 4,
+            })
+            .text(Memory::Itcm)
+            .text_spill(0x1000)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("ITCM_TEXT (RWX) : ORIGIN = 0x20, LENGTH = 0x1000\n"));
+        assert!(script.contains("ITCM (RWX) : ORIGIN = 0x1020, LENGTH = 0x1EFE0\n"));
+        assert!(script.contains("REGION_ALIAS(\"REGION_TEXT\", ITCM_TEXT);"));
+        assert!(script.contains("  .text_spill : ALIGN(4)\n"));
+        assert!(script.contains(".text exceeds its ITCM limit of 4096 bytes."));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .text(Memory::Itcm)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("ITCM_TEXT"));
+        assert!(!script.contains(".text_spill"));
+
+        for builder in [
+            RuntimeBuilder::from_ram(Family::Imxrt1060).text_spill(0x1000),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .text(Memory::Flash)
+                .text_spill(0x1000),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .text(Memory::Itcm)
+                .text_spill(0),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .text(Memory::Itcm)
+                .text_spill(1024 * 1024),
+        ] {
+            assert!(builder.write_linker_script(&mut io::sink()).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_compress() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .text(Memory::Itcm)
+            .compress(Section::Text)
+            .compress(Section::Data)
+            .compress(Section::Text)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("LZ4 (R) : ORIGIN = 0xF0000000, LENGTH = 0x8000000\n"));
+        assert!(script.contains("REGION_ALIAS(\"REGION_LOAD_TEXT_COPY\", LZ4);"));
+        assert!(script.contains("REGION_ALIAS(\"REGION_LOAD_RODATA\", FLASH);"));
+        assert!(script.contains("REGION_ALIAS(\"REGION_LOAD_DATA\", LZ4);"));
+        assert!(script.contains("__text_lz4 = 1;\n__rodata_lz4 = 0;\n__data_lz4 = 1;\n"));
+        assert_eq!(script.matches("    LONG(LOADADDR(.text));\n").count(), 1);
+        assert!(script.contains("    LONG(LOADADDR(.data));\n"));
+        assert!(script.contains("__imxrt_rt_lz4_start = ALIGN("));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("LZ4"));
+        assert!(script.contains("__text_lz4 = 0;"));
+
+        for builder in [
+            RuntimeBuilder::from_ram(Family::Imxrt1060).compress(Section::Data),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024).compress(Section::Bss),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .text(Memory::Flash)
+                .compress(Section::Text),
+        ] {
+            assert!(builder.write_linker_script(&mut io::sink()).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_boot_timestamps() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .boot_timestamps(true)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__boot_timestamps = 1;\n"));
+        assert!(script.contains("__imxrt_rt_boot_timestamps = .;\n"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1180).write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__boot_timestamps = 0;\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_init_arrays() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .init_arrays(true)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__init_arrays = 1;\n"));
+        assert!(script.contains("__init_array_start = .;\n"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1060).write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__init_arrays = 0;\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_libc_symbols() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .libc_symbols(true)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("PROVIDE(__HeapBase = __sheap);\n"));
+        assert!(script.contains("PROVIDE(__StackTop = __sstack);\n"));
+        assert!(script.contains("PROVIDE(_sbrk = __imxrt_rt_sbrk);\n"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1060).write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("_sbrk"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_unwind_tables() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("    *(.ARM.exidx.*);\n"));
+        assert!(!script.contains("__exidx_start"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .unwind_tables(Memory::Dtcm)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("    *(.ARM.exidx.*);\n"));
+        assert!(script.contains("    __exidx_start = .;\n"));
+        assert!(script.contains("  } > DTCM AT> FLASH\n"));
+        assert!(script.contains("    LONG(LOADADDR(.ARM.exidx));\n"));
+
+        let result = RuntimeBuilder::from_ram(Family::Imxrt1060)
+            .unwind_tables(Memory::Flash)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+
+        // The index table can't reach functions in flash from DTCM or ITCM, and it
+        // can't reach functions in ITCM from flash.
+        for builder in [
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .unwind_tables(Memory::Flash)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .text(Memory::Flash)
+                .itcm_functions(&["*fft*"])
+                .unwind_tables(Memory::Flash)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .text(Memory::Flash)
+                .crate_code("micromath", Memory::Itcm)
+                .unwind_tables(Memory::Flash)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .text(Memory::Flash)
+                .unwind_tables(Memory::Dtcm)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .crate_code("micromath", Memory::Flash)
+                .unwind_tables(Memory::Itcm)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .text_spill(64 * 1024)
+                .unwind_tables(Memory::Dtcm)
+                .clone(),
+        ] {
+            assert!(builder.write_linker_script(&mut io::sink()).is_err());
+        }
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .text(Memory::Flash)
+            .unwind_tables(Memory::Ocram)
+            .write_linker_script(&mut io::sink())?;
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .text(Memory::Flash)
+            .unwind_tables(Memory::Flash)
+            .write_linker_script(&mut io::sink())?;
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .crate_code("micromath", Memory::Flash)
+            .unwind_tables(Memory::Ocram)
+            .write_linker_script(&mut io::sink())?;
+        RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024)
+            .unwind_tables(Memory::Flash)
+            .write_linker_script(&mut io::sink())?;
+        let error = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .unwind_tables(Memory::Flash)
+            .write_linker_script(&mut io::sink())
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unwind tables in FLASH can't reach .text in ITCM. Place the unwind tables in ITCM"
+        );
+        RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024)
+            .text(Memory::Flash)
+            .unwind_tables(Memory::Dtcm)
+            .write_linker_script(&mut io::sink())?;
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_reset_reason() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .reset_reason(true)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__reset_reason = 1;\n"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1170).write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__reset_reason = 0;\n"));
+
+        let result = RuntimeBuilder::from_ram(Family::Imxrt1180)
+            .reset_reason(true)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_crash_log() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .crash_log(Memory::Dtcm, 1000)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("    . += ALIGN(0x3E8, 8);\n"));
+        assert!(script.contains("DTCM holds .stack, .crashlog,"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1060).write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("  __scrashlog = 0;\n"));
+
+        assert!(script.contains("__secc_tail = 0;\n"));
+        assert!(script.contains("__eecc_crashlog = 0;\n"));
+
+        // Pre-init zeroes around a crash log in ECC RAM.
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024)
+            .flexram_ecc(FlexRamEcc::Enable)
+            .crash_log(Memory::Dtcm, 1024)
+            .reset_reason(true)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__sdtcm = ORIGIN(DTCM);\n"));
+        assert!(script.contains("__edtcm = __scrashlog;\n"));
+        assert!(script.contains("__secc_tail = __ecrashlog;\n"));
+        assert!(script.contains("__eecc_tail = ORIGIN(DTCM) + LENGTH(DTCM);\n"));
+        assert!(script.contains("__eecc_crashlog = __ecrashlog;\n"));
+        assert!(script.contains("__eitcm = 0x00"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024)
+            .mecc64(Mecc64::Enable)
+            .crash_log(Memory::Ocram, 1024)
+            .reset_reason(true)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__eocram = __scrashlog;\n"));
+        assert!(script.contains("__secc_tail = __ecrashlog;\n"));
+
+        // The runtime can't detect a power-on reset without the reset reason.
+        let result = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024)
+            .flexram_ecc(FlexRamEcc::Enable)
+            .crash_log(Memory::Dtcm, 1024)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+        let result = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .crash_log(Memory::Flash, 1024)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_fault_handler() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .fault_handler(Memory::Dtcm, FaultAction::Handler)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("PROVIDE(BusFault = __imxrt_rt_fault);\n"));
+        assert!(script.contains("__imxrt_rt_fault_hook = FaultRecorded;\n"));
+        assert!(script.contains("DTCM holds .stack, .fault_record,"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1060).write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("__imxrt_rt_fault_hook"));
+        assert!(script.contains("  __imxrt_rt_fault_record = 0;\n"));
+
+        let result = RuntimeBuilder::from_flexspi(Family::Imxrt1170, 16 * 1024)
+            .mecc64(Mecc64::Enable)
+            .fault_handler(Memory::Ocram, FaultAction::Reset)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_position_independent() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::in_flash(Family::Imxrt1060, 1024 * 1024, 0x10_0000)
+            .position_independent(true)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__position_independent = 1;\n"));
+        assert!(script.contains("  .imxrt_rt.got : ALIGN(4)\n"));
+        assert!(script.contains("    LONG(LOADADDR(.vector_table));\n"));
+        assert!(script.contains("    LONG(LOADADDR(.data));\n"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::in_flash(Family::Imxrt1060, 1024 * 1024, 0x10_0000)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("__position_independent = 0;\n"));
+        assert!(script.contains("  __imxrt_rt_sgot = 0;\n"));
+
+        let result = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .position_independent(true)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+
+        let result = RuntimeBuilder::in_flash(Family::Imxrt1060, 1024 * 1024, 0x10_0000)
+            .position_independent(true)
+            .text(Memory::Flash)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+
+        let result = RuntimeBuilder::in_flash(Family::Imxrt1060, 1024 * 1024, 0x10_0000)
+            .position_independent(true)
+            .image_check(ImageCheckFailure::Reset)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_rom_copy() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+            .rom_copy(Memory::Itcm, 64 * 1024)
+            .text(Memory::Flash)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("FLASH (RWX) : ORIGIN = 0x20, LENGTH = 0x10000\n"));
+        assert!(script.contains("ITCM (RWX) : ORIGIN = 0x10020, LENGTH = 0xFFE0\n"));
+        let image_size = script
+            .lines()
+            .find(|line| line.starts_with("__image_size = "))
+            .unwrap();
+        assert!(image_size.contains("LOADADDR(.data) + SIZEOF(.data)"));
+
+        for builder in [
+            RuntimeBuilder::in_flash(Family::Imxrt1060, 1024 * 1024, 0)
+                .rom_copy(Memory::Itcm, 64 * 1024)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1180, 16 * 1024 * 1024)
+                .rom_copy(Memory::Ocram, 64 * 1024)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .rom_copy(Memory::Dtcm, 64 * 1024)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .rom_copy(Memory::Itcm, 8 * 1024)
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .rom_copy(Memory::Itcm, 64 * 1024)
+                .flexram_banks(FlexRamBanks {
+                    ocram: 0,
+                    itcm: 8,
+                    dtcm: 8,
+                })
+                .clone(),
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .rom_copy(Memory::Ocram, 64 * 1024)
+                .image_check(ImageCheckFailure::Reset)
+                .clone(),
+        ] {
+            assert!(builder.write_linker_script(&mut io::sink()).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_serial_download() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1060)
+            .serial_download(Memory::Itcm, 64 * 1024)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("FLASH (RWX) : ORIGIN = 0x20, LENGTH = 0x10000\n"));
+        assert!(script.contains("ITCM (RWX) : ORIGIN = 0x10020, LENGTH = 0xFFE0\n"));
+        assert!(script.contains("Begin imxrt-sdp-header.x"));
+        assert!(script.contains("__image_size = "));
+
+        // Without the option, a RAM image has no boot header.
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1060).write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(!script.contains("FLASH (RWX)"));
+        assert!(!script.contains("__ivt"));
+
+        for builder in [
+            RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024 * 1024)
+                .serial_download(Memory::Itcm, 64 * 1024)
+                .clone(),
+            RuntimeBuilder::from_ram(Family::Imxrt1180)
+                .serial_download(Memory::Ocram, 64 * 1024)
+                .clone(),
+            RuntimeBuilder::from_ram(Family::Imxrt1060)
+                .serial_download(Memory::Dtcm, 64 * 1024)
+                .clone(),
+            RuntimeBuilder::from_ram(Family::Imxrt1060)
+                .serial_download(Memory::Itcm, 4 * 1024)
+                .clone(),
+            RuntimeBuilder::from_ram(Family::Imxrt1060)
+                .serial_download(Memory::Itcm, 64 * 1024)
+                .flexram_banks(FlexRamBanks {
+                    ocram: 0,
+                    itcm: 8,
+                    dtcm: 8,
+                })
+                .clone(),
+        ] {
+            assert!(builder.write_linker_script(&mut io::sink()).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_image_check() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .image_check(ImageCheckFailure::Handler)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("    __imxrt_rt_image_check = .;\n    LONG(ImageCheckFailed);\n"));
+        assert!(script.contains("    LONG(0x00000000); LONG(0x77073096);"));
+        assert!(script.contains("EXTERN(ImageCheckFailed);\n"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("  __imxrt_rt_image_check = 0;\n"));
+        assert!(!script.contains("ImageCheckFailed"));
+
+        let result = RuntimeBuilder::from_ram(Family::Imxrt1060)
+            .image_check(ImageCheckFailure::Spin)
+            .write_linker_script(&mut io::sink());
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_relocated_sections() -> Result<(), Error> {
+        let mut script = Vec::new();
+        RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .flexram_banks(FlexRamBanks {
+                ocram: 8,
+                itcm: 8,
+                dtcm: 0,
+            })
+            .write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("    *(.imxrt_rt.dtcm .imxrt_rt.dtcm.*)\n"));
+        assert!(script.contains("    LONG(LOADADDR(.itcm));\n"));
+        assert!(!script.contains("    LONG(LOADADDR(.dtcm));\n"));
+        assert!(script.contains("Items are placed in DTCM, but there's no DTCM."));
+        assert!(!script.contains("Items are placed in ITCM"));
+
+        let mut script = Vec::new();
+        RuntimeBuilder::from_ram(Family::Imxrt1060).write_linker_script(&mut script)?;
+        let script = String::from_utf8(script)?;
+        assert!(script.contains("  } > OCRAM\n"));
+        assert!(!script.contains("AT> FLASH"));
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_builder_invalid_named_heaps() {
+        for name in ["", "1st", "dsp-heap", "dsp.heap", "dsp heap"] {
+            let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+                .named_heap(name, Memory::Dtcm, 1024)
+                .write_linker_script(&mut io::sink());
+            assert!(res.is_err(), "{name}");
+        }
+
+        let res = RuntimeBuilder::from_flexspi(Family::Imxrt1060, 16 * 1024)
+            .named_heap("dsp", Memory::Flash, 1024)
+            .write_linker_script(&mut io::sink());
+        assert!(res.is_err());
+    }
+
     #[test]
     fn itcm_start_size() {
         // Most parts have an ITCM that could touch address 0.
@@ -0,0 +1,164 @@
+//! Post-link checksums of the flash image.
+//!
+//! When the runtime checks its image, the generated linker script places a
+//! record behind the load image of the vector table. The record holds the
+//! failure action, the start of the checked image, and a CRC table. Adding
+//! the checksum writes the end of the checked image, and its CRC-32, into the
+//! record. The record itself isn't checked.
+
+use std::error::Error;
+
+use super::{IMAGE_CHECK_RECORD_SIZE, LZ4_STAGING_ORIGIN, crc32_table, elf};
+
+/// Add the CRC-32 that the runtime checks before it copies from flash.
+///
+/// `contents` is an ELF file produced from a runtime that checks its image.
+/// Returns a new ELF file with the CRC. Flash the returned program, not the
+/// original program. See the builder's [image checks](crate::RuntimeBuilder#image-checks)
+/// documentation for more information.
+///
+/// ```no_run
+/// let elf = std::fs::read("target/thumbv7em-none-eabihf/release/my-firmware").unwrap();
+/// let checked = imxrt_rt::checksum_image(&elf).unwrap();
+/// std::fs::write("target/thumbv7em-none-eabihf/release/my-firmware", checked).unwrap();
+/// ```
+///
+/// Adding the checksum again produces the same program. This is only available
+/// with the `"checksum"` feature.
+///
+/// # Errors
+///
+/// Returns an error if the contents aren't a 32-bit, little-endian ELF file, or
+/// if the program doesn't check its image. Returns an error if the program has
+/// load images that aren't yet compressed.
+pub fn checksum_image(contents: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let elf = elf::parse(contents)?;
+    let record = elf::symbol(&elf, "__imxrt_rt_image_check")
+        .filter(|&record| record != 0)
+        .ok_or("The program doesn't check its image")?;
+
+    let segments = elf::load_segments(&elf, contents)?;
+    if segments
+        .iter()
+        .any(|segment| segment.paddr >= LZ4_STAGING_ORIGIN)
+    {
+        return Err("Compress the program before adding its checksum".into());
+    }
+    let record_offset = segments
+        .iter()
+        .filter(|segment| {
+            record
+                .checked_add(IMAGE_CHECK_RECORD_SIZE)
+                .is_some_and(|end| end <= segment.paddr_end())
+        })
+        .find_map(|segment| segment.file_offset(record))
+        .ok_or("The image check record isn't loaded")?;
+
+    let start = elf::read_u32(contents, record_offset + 4)?;
+    if record < start {
+        return Err("The image check record precedes the checked image".into());
+    }
+    let end = segments
+        .iter()
+        .filter(|segment| segment.paddr_end() > start)
+        .map(|segment| segment.paddr_end())
+        .max()
+        .unwrap_or(start);
+
+    // Flash that isn't programmed reads as 0xFF.
+    let mut image = vec![0xFF; (end - start) as usize];
+    for segment in &segments {
+        let first = segment.paddr.max(start);
+        let last = segment.paddr_end().min(end);
+        if first < last {
+            let from = (first - segment.paddr) as usize;
+            image[(first - start) as usize..(last - start) as usize]
+                .copy_from_slice(&segment.contents(contents)[from..from + (last - first) as usize]);
+        }
+    }
+
+    let skip = (record - start) as usize;
+    let crc = crc32(!0, &image[..skip]);
+    let crc = !crc32(crc, &image[skip + IMAGE_CHECK_RECORD_SIZE as usize..]);
+
+    let mut output = contents.to_vec();
+    elf::write_u32(&mut output, record_offset + 8, end);
+    elf::write_u32(&mut output, record_offset + 12, crc);
+    Ok(output)
+}
+
+/// Continue a CRC-32 over some bytes.
+fn crc32(crc: u32, bytes: &[u8]) -> u32 {
+    let table = crc32_table();
+    bytes.iter().fold(crc, |crc, &byte| {
+        table[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IMAGE_CHECK_RECORD_SIZE, checksum_image, crc32};
+    use crate::host::elf::{builder::ElfBuilder, read_u32};
+
+    const FLASH: u32 = 0x6000_2000;
+    const RECORD: u32 = FLASH + 8;
+    const TEXT: u32 = FLASH + 0x500;
+
+    /// Build a program with a vector table, an image check record, and
+    /// some instructions behind a gap.
+    fn program() -> ElfBuilder {
+        let mut vectors = Vec::new();
+        for word in [0x2020_0000, 0x6000_2401, 0, FLASH] {
+            vectors.extend_from_slice(&u32::to_le_bytes(word));
+        }
+        vectors.resize(8 + IMAGE_CHECK_RECORD_SIZE as usize, 0);
+        let text: Vec<u8> = (0..16).map(|byte| byte * 3).collect();
+
+        let mut builder = ElfBuilder::default();
+        builder
+            .segment(FLASH, FLASH, &vectors)
+            .segment(TEXT, TEXT, &text)
+            .symbol("__imxrt_rt_image_check", RECORD);
+        builder
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(!crc32(!0, b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn checksum_program() {
+        let builder = program();
+        let checked = checksum_image(&builder.build()).unwrap();
+        let record = builder.offset_of(0) as usize + 8;
+        assert_eq!(read_u32(&checked, record + 8).unwrap(), TEXT + 16);
+
+        let mut image = vec![0xFF; (TEXT + 16 - FLASH) as usize];
+        image[..8].copy_from_slice(&checked[record - 8..record]);
+        image[(TEXT - FLASH) as usize..]
+            .copy_from_slice(&(0..16).map(|b| b * 3).collect::<Vec<u8>>());
+        let record_range = 8..8 + IMAGE_CHECK_RECORD_SIZE as usize;
+        let crc = crc32(!0, &image[..record_range.start]);
+        let crc = !crc32(crc, &image[record_range.end..]);
+        assert_eq!(read_u32(&checked, record + 12).unwrap(), crc);
+
+        // Adding the checksum again doesn't change the program.
+        assert_eq!(checksum_image(&checked).unwrap(), checked);
+    }
+
+    #[test]
+    fn not_an_elf() {
+        assert!(checksum_image(b"not an ELF file").is_err());
+    }
+
+    #[test]
+    fn truncated_record() {
+        // The record is cut off by the end of the segment.
+        let mut builder = ElfBuilder::default();
+        builder
+            .segment(FLASH, FLASH, &[0; 8])
+            .symbol("__imxrt_rt_image_check", FLASH + 4);
+        assert!(checksum_image(&builder.build()).is_err());
+    }
+}
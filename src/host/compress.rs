@@ -0,0 +1,358 @@
+//! Post-link compression of load images.
+//!
+//! The generated linker script stages the load images of compressed sections
+//! in a memory region that doesn't exist. Each staged section has a record in
+//! the copy table. Compression replaces each staged load image with an LZ4
+//! block in flash, placed behind the rest of the flash image, and it rewrites
+//! the copy table records so that `__pre_init` decompresses the blocks.
+
+use std::{error::Error, ops::Range};
+
+use goblin::elf::{
+    program_header::PF_R,
+    section_header::{SHF_ALLOC, SHT_NOBITS, SHT_PROGBITS},
+};
+
+use super::{LZ4_STAGING_LENGTH, LZ4_STAGING_ORIGIN, elf};
+
+/// The size of an ELF32 section header.
+const SECTION_HEADER_SIZE: usize = 40;
+
+/// Compress the load images of a program that's linked with the runtime.
+///
+/// `contents` is an ELF file produced from a runtime that compresses at least one
+/// section. Returns a new ELF file that stores each compressed load image in flash,
+/// in a section named like `.lz4.text`. Flash the returned program, not the original
+/// program. See the builder's [compressed load images](crate::RuntimeBuilder#compressed-load-images)
+/// documentation for more information.
+///
+/// ```no_run
+/// let elf = std::fs::read("target/thumbv7em-none-eabihf/release/my-firmware").unwrap();
+/// let compressed = imxrt_rt::compress_load_images(&elf).unwrap();
+/// std::fs::write("target/thumbv7em-none-eabihf/release/my-firmware", compressed).unwrap();
+/// ```
+///
+/// This is only available with the `"compress"` feature.
+///
+/// # Errors
+///
+/// Returns an error if the contents aren't a 32-bit, little-endian ELF file, if
+/// the program doesn't compress any sections, or if it's already compressed.
+/// Returns an error if the compressed images don't fit in flash.
+pub fn compress_load_images(contents: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let elf = elf::parse(contents)?;
+    let symbol = |name: &str| {
+        elf::symbol(&elf, name).ok_or_else(|| format!("The program has no '{name}' symbol"))
+    };
+
+    let start =
+        symbol("__imxrt_rt_lz4_start").map_err(|_| "The program doesn't compress any sections")?;
+    let flash_end = symbol("__imxrt_rt_FLASH_origin")?
+        .checked_add(symbol("__imxrt_rt_FLASH_length")?)
+        .ok_or("The program's flash region wraps around the address space")?;
+    let table = symbol("__scopy_table")?..symbol("__ecopy_table")?;
+
+    let segments = elf::load_segments(&elf, contents)?;
+    // The copy table's load address is its address.
+    let table_offset = segments
+        .iter()
+        .find_map(|segment| segment.file_offset(table.start))
+        .ok_or("The copy table isn't loaded")?;
+    let table_len = table
+        .end
+        .checked_sub(table.start)
+        .ok_or("The copy table ends before it starts")? as usize;
+    let table_range = table_offset..table_offset + table_len;
+    let table_contents = contents
+        .get(table_range.clone())
+        .ok_or("The copy table is beyond the end of the file")?;
+
+    let mut records = read_records(table_contents)?;
+    let mut blobs: Vec<Blob> = Vec::new();
+    let mut cursor = start;
+    for record in &mut records {
+        let [dst, end, src] = *record;
+        if !(LZ4_STAGING_ORIGIN..LZ4_STAGING_ORIGIN + LZ4_STAGING_LENGTH).contains(&src) {
+            continue;
+        }
+        let Some(segment) = segments
+            .iter()
+            .find(|segment| (segment.paddr..segment.paddr_end()).contains(&src))
+        else {
+            // An empty section has nothing to decompress.
+            if dst != end {
+                return Err(format!(
+                    "The copy table record for {dst:#010X}..{end:#010X} has no staged load image"
+                )
+                .into());
+            }
+            *record = [0; 3];
+            continue;
+        };
+        // Sections that share a segment are decompressed together.
+        if blobs.iter().any(|blob| blob.segment == segment.index) {
+            *record = [0; 3];
+            continue;
+        }
+
+        let data = lz4_flex::block::compress(segment.contents(contents));
+        *record = [segment.vaddr, segment.vaddr + segment.filesz, cursor | 1];
+
+        let name = elf
+            .section_headers
+            .iter()
+            .find(|sec| sec.sh_addr as u32 == segment.vaddr && sec.sh_size > 0)
+            .and_then(|sec| elf.shdr_strtab.get_at(sec.sh_name))
+            .unwrap_or("");
+        let len = u32::try_from(data.len())?;
+        blobs.push(Blob {
+            segment: segment.index,
+            file_range: segment.offset..segment.offset + segment.filesz as usize,
+            name: format!(".lz4{name}"),
+            address: cursor,
+            data,
+        });
+        cursor = cursor
+            .checked_add(len)
+            .and_then(|end| end.checked_next_multiple_of(4))
+            .ok_or("The compressed load images wrap around the address space")?;
+    }
+
+    if blobs.is_empty() {
+        return Err("The program has no staged load images. Is it already compressed?".into());
+    }
+    if cursor > flash_end {
+        return Err(format!(
+            "The compressed load images end at {cursor:#010X}, beyond the end of flash at {flash_end:#010X}"
+        )
+        .into());
+    }
+
+    let mut output = contents.to_vec();
+    write_records(&mut output[table_range], &records);
+
+    // Each staged segment now loads its compressed image into flash.
+    let phoff = elf.header.e_phoff as usize;
+    let phentsize = elf.header.e_phentsize as usize;
+    let mut offsets = Vec::new();
+    for blob in &blobs {
+        align_to_4(&mut output);
+        let offset = output.len() as u32;
+        offsets.push(offset);
+        output.extend_from_slice(&blob.data);
+
+        let header = phoff + blob.segment * phentsize;
+        let len = blob.data.len() as u32;
+        for (field, value) in [offset, blob.address, blob.address, len, len, PF_R, 4]
+            .into_iter()
+            .enumerate()
+        {
+            elf::write_u32(&mut output, header + 4 + 4 * field, value);
+        }
+    }
+
+    // Describe the images with new sections. The section names are appended
+    // to a copy of the section name table.
+    let shstrndx = elf.header.e_shstrndx as usize;
+    let shstrtab = elf
+        .section_headers
+        .get(shstrndx)
+        .ok_or("The program has no section name table")?;
+    let mut names = (shstrtab.sh_offset as usize)
+        .checked_add(shstrtab.sh_size as usize)
+        .and_then(|end| contents.get(shstrtab.sh_offset as usize..end))
+        .ok_or("The section name table is beyond the end of the file")?
+        .to_vec();
+    let mut new_headers = Vec::new();
+    for (blob, offset) in blobs.iter().zip(offsets) {
+        new_headers.push([
+            names.len() as u32,
+            SHT_PROGBITS,
+            SHF_ALLOC,
+            blob.address,
+            offset,
+            blob.data.len() as u32,
+            0,
+            0,
+            4,
+            0,
+        ]);
+        names.extend_from_slice(blob.name.as_bytes());
+        names.push(0);
+    }
+    let names_offset = output.len() as u32;
+    output.extend_from_slice(&names);
+
+    align_to_4(&mut output);
+    let shoff = elf.header.e_shoff as usize;
+    let shnum = elf.header.e_shnum as usize;
+    let new_shoff = output.len();
+    output.extend_from_within(shoff..shoff + shnum * SECTION_HEADER_SIZE);
+    let shstrtab_header = new_shoff + shstrndx * SECTION_HEADER_SIZE;
+    elf::write_u32(&mut output, shstrtab_header + 16, names_offset);
+    elf::write_u32(&mut output, shstrtab_header + 20, names.len() as u32);
+    // The sections that described the staged images are no longer loaded. Without
+    // file contents, objcopy doesn't mistake them for load images at their RAM addresses.
+    for (index, sec) in elf.section_headers.iter().enumerate() {
+        let staged = blobs
+            .iter()
+            .any(|blob| blob.file_range.contains(&(sec.sh_offset as usize)));
+        if staged && sec.sh_type == SHT_PROGBITS && sec.sh_flags & u64::from(SHF_ALLOC) != 0 {
+            elf::write_u32(
+                &mut output,
+                new_shoff + index * SECTION_HEADER_SIZE + 4,
+                SHT_NOBITS,
+            );
+        }
+    }
+    for header in new_headers {
+        for value in header {
+            output.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    elf::write_u32(&mut output, 32, new_shoff as u32);
+    let new_shnum = u16::try_from(shnum + blobs.len()).map_err(|_| "Too many sections")?;
+    output[48..50].copy_from_slice(&new_shnum.to_le_bytes());
+
+    Ok(output)
+}
+
+/// A compressed load image.
+struct Blob {
+    /// Index of the program header that loads this image.
+    segment: usize,
+    /// The file contents of the staged image.
+    file_range: Range<usize>,
+    /// The name of the section that describes this image.
+    name: String,
+    /// The image's address in flash.
+    address: u32,
+    data: Vec<u8>,
+}
+
+fn read_records(table: &[u8]) -> Result<Vec<[u32; 3]>, Box<dyn Error>> {
+    table
+        .chunks_exact(12)
+        .map(|record| {
+            Ok([
+                elf::read_u32(record, 0)?,
+                elf::read_u32(record, 4)?,
+                elf::read_u32(record, 8)?,
+            ])
+        })
+        .collect()
+}
+
+fn write_records(table: &mut [u8], records: &[[u32; 3]]) {
+    for (chunk, record) in table.chunks_exact_mut(12).zip(records) {
+        for (field, value) in record.iter().enumerate() {
+            elf::write_u32(chunk, 4 * field, *value);
+        }
+    }
+}
+
+fn align_to_4(bytes: &mut Vec<u8>) {
+    bytes.resize(bytes.len().next_multiple_of(4), 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use goblin::elf::{
+        Elf,
+        program_header::PT_LOAD,
+        section_header::{SHF_ALLOC, SHT_NOBITS, SHT_PROGBITS},
+    };
+
+    use super::{LZ4_STAGING_ORIGIN, compress_load_images};
+    use crate::host::elf::{builder::ElfBuilder, read_u32};
+
+    const FLASH: u32 = 0x6000_0000;
+    const OCRAM: u32 = 0x2020_0000;
+
+    /// Build a program with one staged section, and a copy table in flash.
+    fn program(flash_length: u32, image: &[u8]) -> Vec<u8> {
+        let mut table = Vec::new();
+        for word in [OCRAM, OCRAM + image.len() as u32, LZ4_STAGING_ORIGIN] {
+            table.extend_from_slice(&word.to_le_bytes());
+        }
+        ElfBuilder::default()
+            .segment(FLASH, FLASH, &table)
+            .segment(OCRAM, LZ4_STAGING_ORIGIN, image)
+            .section(".copy_table", 0)
+            .section(".data", 1)
+            .symbol("__imxrt_rt_lz4_start", FLASH + 12)
+            .symbol("__imxrt_rt_FLASH_origin", FLASH)
+            .symbol("__imxrt_rt_FLASH_length", flash_length)
+            .symbol("__scopy_table", FLASH)
+            .symbol("__ecopy_table", FLASH + 12)
+            .build()
+    }
+
+    #[test]
+    fn compress_staged_section() {
+        let image: Vec<u8> = (0..1024u32).map(|byte| (byte % 7) as u8).collect();
+        let compressed = compress_load_images(&program(0x1000, &image)).unwrap();
+
+        let elf = Elf::parse(&compressed).unwrap();
+        let segment = &elf.program_headers[1];
+        assert_eq!(segment.p_vaddr, u64::from(FLASH + 12));
+        assert_eq!(segment.p_paddr, u64::from(FLASH + 12));
+        assert!(segment.p_filesz < image.len() as u64);
+
+        let offset = segment.p_offset as usize;
+        let block = &compressed[offset..offset + segment.p_filesz as usize];
+        assert_eq!(
+            lz4_flex::block::decompress(block, image.len()).unwrap(),
+            image
+        );
+
+        let table = elf.program_headers[0].p_offset as usize;
+        let record = [0, 4, 8].map(|field| read_u32(&compressed, table + field).unwrap());
+        assert_eq!(record, [OCRAM, OCRAM + 1024, (FLASH + 12) | 1]);
+
+        let section = elf
+            .section_headers
+            .iter()
+            .find(|sec| elf.shdr_strtab.get_at(sec.sh_name) == Some(".lz4.data"))
+            .unwrap();
+        assert_eq!(section.sh_offset, segment.p_offset);
+        assert_eq!(section.sh_size, segment.p_filesz);
+
+        // Only the compressed images are loaded.
+        let data = elf
+            .section_headers
+            .iter()
+            .find(|sec| elf.shdr_strtab.get_at(sec.sh_name) == Some(".data"))
+            .unwrap();
+        assert_eq!(data.sh_type, SHT_NOBITS);
+        for sec in &elf.section_headers {
+            if sec.sh_type != SHT_PROGBITS
+                || sec.sh_flags & u64::from(SHF_ALLOC) == 0
+                || sec.sh_size == 0
+            {
+                continue;
+            }
+            assert!(
+                elf.program_headers.iter().any(|ph| {
+                    ph.p_type == PT_LOAD
+                        && ph.p_vaddr <= sec.sh_addr
+                        && sec.sh_addr + sec.sh_size <= ph.p_vaddr + ph.p_filesz
+                        && ph.p_offset <= sec.sh_offset
+                        && sec.sh_offset + sec.sh_size <= ph.p_offset + ph.p_filesz
+                }),
+                "{:?} isn't loaded",
+                elf.shdr_strtab.get_at(sec.sh_name)
+            );
+        }
+
+        // Nothing is left to compress.
+        assert!(compress_load_images(&compressed).is_err());
+    }
+
+    #[test]
+    fn compressed_images_must_fit_in_flash() {
+        let image: Vec<u8> = (0..1024u32).map(|byte| (byte * 37 % 251) as u8).collect();
+        assert!(compress_load_images(&program(0x100, &image)).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! ELF plumbing for the post-link tools.
+//!
+//! The tools only handle 32-bit, little-endian programs. Their inputs may be
+//! malformed, so everything that's read from the file is bounds checked.
+
+use std::error::Error;
+
+use goblin::elf::{Elf, program_header::PT_LOAD};
+
+/// Parse a 32-bit, little-endian ELF file.
+pub(crate) fn parse(contents: &[u8]) -> Result<Elf<'_>, Box<dyn Error>> {
+    let elf = Elf::parse(contents)?;
+    if elf.is_64 || !elf.little_endian {
+        return Err("Expected a 32-bit, little-endian ELF file".into());
+    }
+    Ok(elf)
+}
+
+/// Returns the value of the named symbol.
+pub(crate) fn symbol(elf: &Elf, name: &str) -> Option<u32> {
+    elf.syms
+        .iter()
+        .find(|sym| elf.strtab.get_at(sym.st_name) == Some(name))
+        .map(|sym| sym.st_value as u32)
+}
+
+/// A loadable segment with file contents.
+pub(crate) struct Segment {
+    /// Index of the segment's program header.
+    #[cfg_attr(not(feature = "compress"), allow(dead_code))]
+    pub(crate) index: usize,
+    pub(crate) offset: usize,
+    pub(crate) vaddr: u32,
+    pub(crate) paddr: u32,
+    pub(crate) filesz: u32,
+}
+
+impl Segment {
+    /// The physical address behind the end of the segment.
+    pub(crate) fn paddr_end(&self) -> u32 {
+        self.paddr + self.filesz
+    }
+
+    /// The segment's file contents.
+    pub(crate) fn contents<'a>(&self, contents: &'a [u8]) -> &'a [u8] {
+        &contents[self.offset..self.offset + self.filesz as usize]
+    }
+
+    /// Returns the file offset of the physical address, if it's in this segment.
+    pub(crate) fn file_offset(&self, paddr: u32) -> Option<usize> {
+        (self.paddr..self.paddr_end())
+            .contains(&paddr)
+            .then(|| self.offset + (paddr - self.paddr) as usize)
+    }
+}
+
+/// Returns the loadable segments that have file contents.
+///
+/// Returns an error if a segment's contents aren't in the file, or if the
+/// segment wraps around the address space.
+pub(crate) fn load_segments(elf: &Elf, contents: &[u8]) -> Result<Vec<Segment>, Box<dyn Error>> {
+    elf.program_headers
+        .iter()
+        .enumerate()
+        .filter(|(_, ph)| ph.p_type == PT_LOAD && ph.p_filesz > 0)
+        .map(|(index, ph)| {
+            let segment = Segment {
+                index,
+                offset: usize::try_from(ph.p_offset)?,
+                vaddr: u32::try_from(ph.p_vaddr)?,
+                paddr: u32::try_from(ph.p_paddr)?,
+                filesz: u32::try_from(ph.p_filesz)?,
+            };
+            let in_file = segment
+                .offset
+                .checked_add(segment.filesz as usize)
+                .is_some_and(|end| end <= contents.len());
+            if !in_file
+                || segment.vaddr.checked_add(segment.filesz).is_none()
+                || segment.paddr.checked_add(segment.filesz).is_none()
+            {
+                return Err(format!("Program header {index} is malformed").into());
+            }
+            Ok(segment)
+        })
+        .collect()
+}
+
+/// Read a little-endian word from the file.
+pub(crate) fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, Box<dyn Error>> {
+    offset
+        .checked_add(4)
+        .and_then(|end| bytes.get(offset..end))
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+        .ok_or_else(|| format!("Offset {offset:#X} is beyond the end of the file").into())
+}
+
+/// Write a little-endian word into the file.
+///
+/// The offset has already been checked.
+#[cfg(any(feature = "compress", feature = "checksum"))]
+pub(crate) fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Builds ELF files for tests.
+#[cfg(test)]
+pub(crate) mod builder {
+    use goblin::elf::section_header::{SHF_ALLOC, SHT_PROGBITS, SHT_STRTAB, SHT_SYMTAB};
+
+    /// The size of the ELF header.
+    const EHSIZE: u32 = 52;
+    /// The size of a program header.
+    const PHENTSIZE: u32 = 32;
+
+    /// Describes a program with some segments, sections, and absolute symbols.
+    #[derive(Default)]
+    pub(crate) struct ElfBuilder {
+        segments: Vec<(u32, u32, Vec<u8>)>,
+        sections: Vec<(String, usize)>,
+        symbols: Vec<(String, u32)>,
+    }
+
+    impl ElfBuilder {
+        /// Add a loadable segment at the virtual and physical addresses.
+        pub(crate) fn segment(&mut self, vaddr: u32, paddr: u32, contents: &[u8]) -> &mut Self {
+            self.segments.push((vaddr, paddr, contents.to_vec()));
+            self
+        }
+
+        /// Add an allocated section that covers an entire segment.
+        pub(crate) fn section(&mut self, name: &str, segment: usize) -> &mut Self {
+            self.sections.push((name.into(), segment));
+            self
+        }
+
+        /// Add an absolute symbol.
+        pub(crate) fn symbol(&mut self, name: &str, value: u32) -> &mut Self {
+            self.symbols.push((name.into(), value));
+            self
+        }
+
+        /// Returns the file offset of a segment's contents.
+        pub(crate) fn offset_of(&self, segment: usize) -> u32 {
+            let mut offset = EHSIZE + PHENTSIZE * self.segments.len() as u32;
+            for (_, _, contents) in &self.segments[..segment] {
+                offset = (offset + contents.len() as u32).next_multiple_of(4);
+            }
+            offset
+        }
+
+        pub(crate) fn build(&self) -> Vec<u8> {
+            fn words(bytes: &mut Vec<u8>, words: &[u32]) {
+                for word in words {
+                    bytes.extend_from_slice(&word.to_le_bytes());
+                }
+            }
+            fn align(bytes: &mut Vec<u8>) {
+                bytes.resize(bytes.len().next_multiple_of(4), 0);
+            }
+            fn name(table: &mut Vec<u8>, name: &str) -> u32 {
+                let offset = table.len() as u32;
+                table.extend_from_slice(name.as_bytes());
+                table.push(0);
+                offset
+            }
+
+            let phnum = self.segments.len() as u16;
+            let shnum = self.sections.len() as u16 + 4;
+            let mut elf = Vec::new();
+            elf.extend_from_slice(b"\x7fELF\x01\x01\x01\0\0\0\0\0\0\0\0\0");
+            elf.extend_from_slice(&[2, 0, 40, 0]);
+            words(&mut elf, &[1, 0, EHSIZE, 0, 0x0500_0000]);
+            for half in [EHSIZE as u16, PHENTSIZE as u16, phnum, 40, shnum, shnum - 1] {
+                elf.extend_from_slice(&half.to_le_bytes());
+            }
+
+            for (segment, (vaddr, paddr, contents)) in self.segments.iter().enumerate() {
+                let len = contents.len() as u32;
+                let offset = self.offset_of(segment);
+                words(&mut elf, &[1, offset, *vaddr, *paddr, len, len, 4, 4]);
+            }
+            for (_, _, contents) in &self.segments {
+                elf.extend_from_slice(contents);
+                align(&mut elf);
+            }
+
+            let mut strtab = vec![0];
+            let symtab_offset = elf.len() as u32;
+            elf.extend_from_slice(&[0; 16]);
+            for (symbol, value) in &self.symbols {
+                words(&mut elf, &[name(&mut strtab, symbol), *value, 0]);
+                elf.extend_from_slice(&[0, 0, 0xf1, 0xff]);
+            }
+            let strtab_offset = elf.len() as u32;
+            elf.extend_from_slice(&strtab);
+            align(&mut elf);
+
+            let mut shstrtab = vec![0];
+            let mut headers = vec![[0; 10]];
+            for (section, segment) in &self.sections {
+                let (vaddr, _, contents) = &self.segments[*segment];
+                headers.push([
+                    name(&mut shstrtab, section),
+                    SHT_PROGBITS,
+                    SHF_ALLOC,
+                    *vaddr,
+                    self.offset_of(*segment),
+                    contents.len() as u32,
+                    0,
+                    0,
+                    4,
+                    0,
+                ]);
+            }
+            let strtab_index = headers.len() as u32 + 1;
+            let symtab_size = 16 * (self.symbols.len() as u32 + 1);
+            headers.push([
+                name(&mut shstrtab, ".symtab"),
+                SHT_SYMTAB,
+                0,
+                0,
+                symtab_offset,
+                symtab_size,
+                strtab_index,
+                1,
+                4,
+                16,
+            ]);
+            headers.push([
+                name(&mut shstrtab, ".strtab"),
+                SHT_STRTAB,
+                0,
+                0,
+                strtab_offset,
+                strtab.len() as u32,
+                0,
+                0,
+                1,
+                0,
+            ]);
+            let shstrtab_name = name(&mut shstrtab, ".shstrtab");
+            let shstrtab_offset = elf.len() as u32;
+            elf.extend_from_slice(&shstrtab);
+            align(&mut elf);
+            headers.push([
+                shstrtab_name,
+                SHT_STRTAB,
+                0,
+                0,
+                shstrtab_offset,
+                shstrtab.len() as u32,
+                0,
+                0,
+                1,
+                0,
+            ]);
+
+            let shoff = elf.len() as u32;
+            elf[32..36].copy_from_slice(&shoff.to_le_bytes());
+            for header in headers {
+                words(&mut elf, &header);
+            }
+            elf
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builder::ElfBuilder, load_segments, parse, read_u32, symbol};
+
+    #[test]
+    fn segments_and_symbols() {
+        let mut builder = ElfBuilder::default();
+        builder
+            .segment(0x2000_0000, 0x6000_0000, &[1, 2, 3, 4])
+            .section(".data", 0)
+            .symbol("__answer", 42);
+        let contents = builder.build();
+        let elf = parse(&contents).unwrap();
+        assert_eq!(symbol(&elf, "__answer"), Some(42));
+        assert_eq!(symbol(&elf, "__question"), None);
+        let data = elf
+            .section_headers
+            .iter()
+            .find(|sec| elf.shdr_strtab.get_at(sec.sh_name) == Some(".data"))
+            .unwrap();
+        assert_eq!(data.sh_addr, 0x2000_0000);
+        assert_eq!(data.sh_offset, u64::from(builder.offset_of(0)));
+
+        let segments = load_segments(&elf, &contents).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].contents(&contents), &[1, 2, 3, 4]);
+        assert_eq!(
+            segments[0].file_offset(0x6000_0003),
+            Some(segments[0].offset + 3)
+        );
+        assert_eq!(segments[0].file_offset(0x6000_0004), None);
+        assert_eq!(
+            read_u32(&contents, segments[0].offset).unwrap(),
+            0x0403_0201
+        );
+        assert!(read_u32(&contents, contents.len() - 3).is_err());
+        assert!(read_u32(&contents, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn malformed_segments() {
+        let mut builder = ElfBuilder::default();
+        builder.segment(0, 0xFFFF_FFFE, &[0; 4]);
+        let contents = builder.build();
+        let elf = parse(&contents).unwrap();
+        assert!(load_segments(&elf, &contents).is_err());
+
+        // The segment's contents are beyond the end of the file.
+        let mut builder = ElfBuilder::default();
+        builder.segment(0, 0, &[0; 4]);
+        let mut contents = builder.build();
+        contents[52 + 16..52 + 20].copy_from_slice(&0x1000_0000u32.to_le_bytes());
+        let elf = parse(&contents).unwrap();
+        assert!(load_segments(&elf, &contents).is_err());
+    }
+}
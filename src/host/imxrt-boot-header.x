@@ -3,14 +3,11 @@
  * runtime configuration.
  */
 
-/* If you're ever playing with the boot ROM copy, this is your image size.
- *
- * Note that it depends on the section layout! Need to represent contiguous
- * sections starting from the boot header.
+/* The host defines __image_size, the size of the image from the start of
+ * flash through the end of its last load image. The boot ROM copies this
+ * many bytes if the runtime lets it copy the image.
  */
-__image_size = SIZEOF(.boot) + SIZEOF(.vector_table) + SIZEOF(.xip) + SIZEOF(.text) + SIZEOF(.rodata);
 
-/* END TODO */
 EXTERN(FLEXSPI_CONFIGURATION_BLOCK);
 
 /* # Sections */
@@ -32,9 +29,10 @@ SECTIONS
    * start of flash too. This represents the entire boot header,
    * including the FCB, IVT, and boot data. (NXP docs say that the
    * initial load region is 4K; my testing shows that it's 8K, and
-   * this aligns with observations of others.) If you ever want to
-   * try this, make sure you're specifing the VMA and LMA of the
-   * boot head section to represent this 8K relocation.
+   * this aligns with observations of others.) When the runtime
+   * lets the boot ROM copy the image, the FLASH region describes
+   * the copy in RAM, so the boot header is linked at the start of
+   * the copy.
    */
   .boot ORIGIN(FLASH):
   {
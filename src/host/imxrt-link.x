@@ -17,6 +17,10 @@ EXTERN(__EXCEPTIONS); /* depends on all the these PROVIDED symbols */
 EXTERN(DefaultHandler);
 EXTERN(__pre_init);
 
+/* The user's pre-init function is optional. */
+EXTERN(__imxrt_rt_user_pre_init);
+PROVIDE(__imxrt_rt_user_pre_init = 0);
+
 PROVIDE(NonMaskableInt = DefaultHandler);
 EXTERN(HardFaultTrampoline);
 PROVIDE(MemoryManagement = DefaultHandler);
@@ -42,14 +46,16 @@ SECTIONS
     KEEP(*(.bootrom_reservation));
   } > OCRAM
 
-  .stack (NOLOAD) : ALIGN(8)
-  {
-    __estack = .;
-    . += ALIGN(__stack_size, 8);
-    __sstack = .;
-    /* Symbol expected by cortex-m-rt */
-    _stack_start = __sstack;
-  } > REGION_STACK
+  /* The stack is placed here, at the start of its region, unless it
+     consumes the remainder of its region. */
+  /* imxrt-rt-generated(stack) */
+
+  /* The crash log, if there is one. It's never copied, and it's only zeroed
+     in ECC RAM after a power-on reset. */
+  /* imxrt-rt-generated(crash_log) */
+
+  /* The fault handler's record, if there is one. It's never copied or zeroed. */
+  /* imxrt-rt-generated(fault_record) */
 
   .vector_table : ALIGN(1024)
   {
@@ -72,7 +78,11 @@ SECTIONS
     KEEP(*(.vector_table.interrupts)); /* this is the `__INTERRUPTS` symbol */
     __evector_table = .;
   } > REGION_VTABLE AT> REGION_LOAD_VTABLE
-  __sivector_table = LOADADDR(.vector_table);
+  /* A position-independent image copies its vector table with the copy table. */
+  __sivector_table = __position_independent ? ADDR(.vector_table) : LOADADDR(.vector_table);
+
+  /* The record for the image check, if the runtime checks its image. */
+  /* imxrt-rt-generated(image_check) */
 
   /* This section guarantees VMA = LMA to allow the execute-in-place entry point to be inside the image. */
   .xip : ALIGN(4)
@@ -80,7 +90,24 @@ SECTIONS
     /* Included here if not otherwise included in the boot header. */
     *(.Reset);
     *(.__pre_init);
+    /* Items placed in flash. A position-independent image can't have any. */
+    __imxrt_rt_sxip_items = .;
     *(.xip .xip.*);
+    *(.imxrt_rt.flash .imxrt_rt.flash.*);
+    /* imxrt-rt-generated(flash_crates) */
+    __imxrt_rt_exip_items = .;
+  } > REGION_LOAD_TEXT
+
+  /* Items that are copied into ITCM, DTCM, and OCRAM, no matter the placement of
+     .text and .data. These precede .text so that functions aren't captured by .text. */
+  /* imxrt-rt-generated(relocated) */
+
+  /* Records for sections that are copied by __pre_init. */
+  .copy_table : ALIGN(4)
+  {
+    __scopy_table = .;
+    /* imxrt-rt-generated(copy_table) */
+    __ecopy_table = .;
   } > REGION_LOAD_TEXT
 
   .text : ALIGN(4)
@@ -94,14 +121,32 @@ SECTIONS
     *(.HardFault.*);
     . = ALIGN(4); /* Pad .text to the alignment to workaround overlapping load section bug in old lld */
     __etext = .;
-  } > REGION_TEXT AT> REGION_LOAD_TEXT
-  __sitext = LOADADDR(.text);
+  } > REGION_TEXT AT> REGION_LOAD_TEXT_COPY
+  /* A compressed section is decompressed by the copy table, and a position-independent
+     image copies its sections with the copy table. Skip the copy. */
+  __sitext = (__text_lz4 || __position_independent) ? ADDR(.text) : LOADADDR(.text);
+
+  /* Code that doesn't fit in .text, if .text may spill into flash. */
+  /* imxrt-rt-generated(text_spill) */
 
   .rodata : ALIGN(4)
   {
     FILL(0xff);
     . = ALIGN(4);
     __srodata = .;
+    /* Static constructors and destructors. .preinit_array is adjacent to
+       .init_array, so pre-init runs them in a single pass. */
+    __preinit_array_start = .;
+    KEEP(*(.preinit_array));
+    __preinit_array_end = .;
+    __init_array_start = .;
+    KEEP(*(SORT(.init_array.*)));
+    KEEP(*(.init_array));
+    __init_array_end = .;
+    __fini_array_start = .;
+    KEEP(*(SORT(.fini_array.*)));
+    KEEP(*(.fini_array));
+    __fini_array_end = .;
     *(.rodata .rodata.*);
 
     /* 4-byte align the end (VMA) of this section.
@@ -110,7 +155,13 @@ SECTIONS
     . = ALIGN(4);
     __erodata = .;
   } > REGION_RODATA AT> REGION_LOAD_RODATA
-  __sirodata = LOADADDR(.rodata);
+  __sirodata = (__rodata_lz4 || __position_independent) ? ADDR(.rodata) : LOADADDR(.rodata);
+
+  /* ARM unwind tables, if they're kept. Otherwise, they're discarded. */
+  /* imxrt-rt-generated(unwind_tables) */
+
+  /* The global offset table, if the image is position independent. */
+  /* imxrt-rt-generated(got) */
 
   .data : ALIGN(4)
   {
@@ -121,22 +172,38 @@ SECTIONS
     . = ALIGN(4); /* 4-byte align the end (VMA) of this section */
     __edata = .;
   } > REGION_DATA AT> REGION_LOAD_DATA
-  __sidata = LOADADDR(.data);
+  /* If .data is compressed, or if the image is position independent, the copy
+     table initializes .data. */
+  __imxrt_rt_sidata = (__data_lz4 || __position_independent) ? ADDR(.data) : LOADADDR(.data);
+  /* Pre-init initializes .data and .bss if it runs static constructors, or if
+     it records the boot timestamp for main. */
+  __imxrt_rt_init_statics = __init_arrays || __boot_timestamps;
+  /* If .data is compressed, or if pre-init initializes .data, cortex-m-rt
+     copies .data onto itself. */
+  __sidata = (__data_lz4 || __imxrt_rt_init_statics) ? ADDR(.data) : __imxrt_rt_sidata;
 
   .bss (NOLOAD) : ALIGN(4)
   {
     . = ALIGN(4);
-    __sbss = .;
+    __imxrt_rt_sbss = .;
     *(.bss .bss.*);
     *(COMMON); /* Uninitialized C statics */
     . = ALIGN(4); /* 4-byte align the end (VMA) of this section */
       __ebss = .;
   } > REGION_BSS
+  /* If pre-init zeroes .bss, cortex-m-rt sees an empty .bss. */
+  __sbss = __imxrt_rt_init_statics ? __ebss : __imxrt_rt_sbss;
 
   .uninit (NOLOAD) : ALIGN(4)
   {
     . = ALIGN(4);
     __suninit = .;
+    /* Boot timestamps, if enabled. The size matches the target's BootTimestamps. */
+    __imxrt_rt_boot_timestamps = .;
+    . += __boot_timestamps ? 36 : 0;
+    /* The reset status captured by pre-init, if enabled. */
+    __imxrt_rt_reset_status = .;
+    . += __reset_reason ? 4 : 0;
     *(.uninit .uninit.*);
     . = ALIGN(4);
     __euninit = .;
@@ -145,10 +212,17 @@ SECTIONS
   .heap (NOLOAD) : ALIGN(4)
   {
     __sheap = .;
-    . += ALIGN(__heap_size, 4);
+    /* If the heap consumes the remainder of its region, __heap_size is the minimum size. */
+    . = __heap_remainder ? ORIGIN(REGION_HEAP) + LENGTH(REGION_HEAP) : . + ALIGN(__heap_size, 4);
     __eheap = .;
   } > REGION_HEAP
 
+  /* imxrt-rt-generated(heaps) */
+
+  /* If the stack consumes the remainder of its region, it's placed here,
+     behind all other sections in its region. */
+  /* imxrt-rt-generated(stack_remainder) */
+
   /* Dynamic relocations are unsupported. This section is only used to detect relocatable code in
      the input files and raise an error if relocatable code is found */
   .got (NOLOAD) :
@@ -158,10 +232,8 @@ SECTIONS
 
   /DISCARD/ :
   {
-    /* Unused exception related info that only wastes space */
-    *(.ARM.exidx);
-    *(.ARM.exidx.*);
-    *(.ARM.extab.*);
+    /* Unused exception related info that only wastes space, unless it's kept */
+    /* imxrt-rt-generated(discard_unwind_tables) */
   }
 }
 
@@ -183,6 +255,22 @@ BUG(imxrt-rt): .bss is not 4-byte aligned");
 ASSERT(__sheap % 4 == 0, "
 BUG(imxrt-rt): start of .heap is not 4-byte aligned");
 
+/* # Size checks */
+
+ASSERT(__sstack - __estack >= __stack_size, "
+ERROR(imxrt-rt): .stack is smaller than its minimum size.
+The stack consumes the remainder of its region, but the other sections in that
+region leave too little space. Move other sections out of the stack's region,
+reduce the minimum size given to RuntimeBuilder::stack_size_remainder, or give
+the stack a fixed size with RuntimeBuilder::stack_size.");
+
+ASSERT(__eheap - __sheap >= __heap_size, "
+ERROR(imxrt-rt): .heap is smaller than its minimum size.
+The heap consumes the remainder of its region, but the other sections in that
+region leave too little space. Move other sections out of the heap's region,
+reduce the minimum size given to RuntimeBuilder::heap_size_remainder, or give
+the heap a fixed size with RuntimeBuilder::heap_size.");
+
 /* # Position checks */
 
 /* ## .vector_table */
@@ -208,6 +296,15 @@ Dynamic relocations are not supported. If you are linking to C code compiled usi
 the 'cc' crate then modify your build script to compile the C code _without_
 the -fPIC flag. See the documentation of the `cc::Build.pic` method for details.");
 
+/* # Region checks */
+/* imxrt-rt-generated(region_checks) */
+
+/* # Budget checks */
+/* imxrt-rt-generated(budgets) */
+
+/* # Compressed load images */
+/* imxrt-rt-generated(lz4) */
+
 /* Do not exceed this mark in the error messages above                                    | */
 
 /* ===--- End imxrt-link.x ---=== */
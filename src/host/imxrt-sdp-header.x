@@ -0,0 +1,58 @@
+/* ===--- Begin imxrt-sdp-header.x ---===
+ * This extra content is injected into the linker script depending on the
+ * runtime configuration.
+ */
+
+/* # Sections */
+SECTIONS
+{
+  /* Boot header for the serial downloader.
+   *
+   * The serial downloader writes the image into RAM, then jumps to
+   * the image vector table. The FLASH region describes the image in
+   * RAM. Like the boot headers of other non-XIP boot devices, the IVT
+   * is 1K from the start of the image.
+   */
+  .boot ORIGIN(FLASH):
+  {
+    . = ORIGIN(FLASH) + 0x400;
+    /* ------------------
+     * Image vector table
+     * ------------------
+     */
+    __ivt = .;
+    LONG(0x402000D1);           /* Header, magic number */
+    LONG(__sivector_table);     /* Address of the vectors table */
+    LONG(0x00000000);           /* RESERVED */
+    LONG(__dcd);                /* Device Configuration Data */
+    LONG(__boot_data);          /* Address to boot data */
+    LONG(__ivt);                /* Self reference */
+    LONG(0x00000000);           /* Command Sequence File (unused) */
+    LONG(0x00000000);           /* RESERVED */
+    /* ---------
+      * Boot data
+      * ---------
+      */
+    __boot_data = .;
+    LONG(ORIGIN(FLASH));        /* Start of image */
+    LONG(__image_size);         /* Length of image */
+    LONG(0x00000000);           /* Plugin flag (unused) */
+    LONG(0xDEADBEEF);           /* Dummy to align boot data to 16 bytes */
+    . = ALIGN(4);
+    __dcd_start = .;
+    KEEP(*(.dcd));              /* Device Configuration Data */
+    __dcd_end = .;
+    __dcd = ((__dcd_end - __dcd_start) > 0) ? __dcd_start : ABSOLUTE(0);
+  } > FLASH
+
+  /* The serial downloader doesn't use a FlexSPI configuration block. */
+  /DISCARD/ :
+  {
+    *(.fcb);
+  }
+}
+
+ASSERT((__dcd_end - __dcd_start) % 4 == 0, "
+ERROR(imxrt-rt): .dcd (Device Configuration Data) size must be a multiple of 4 bytes.");
+
+/* ===--- End imxrt-sdp-header.x ---=== */
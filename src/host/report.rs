@@ -0,0 +1,488 @@
+//! Post-link memory usage reports.
+//!
+//! The generated linker script records the memory regions, FlexRAM
+//! configuration, and chip family in the program's symbol table. The
+//! report reads those symbols, along with the section headers, to
+//! describe how a linked program uses memory.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Write as _},
+};
+
+use goblin::elf::{
+    Elf, program_header::PT_LOAD, section_header::SHF_ALLOC, section_header::SHT_NOBITS,
+};
+
+use super::{Family, FlexRamKind, Memory, RamRequirements, layout_count_of};
+
+/// Memory usage of a linked program.
+///
+/// Use [`from_elf`](Self::from_elf) to produce a report from a program
+/// that's linked with the runtime. Use the `Display` implementation to
+/// print a human-readable report, or use [`to_json`](Self::to_json) to
+/// produce a machine-readable report.
+///
+/// ```no_run
+/// use imxrt_rt::MemoryReport;
+///
+/// let elf = std::fs::read("target/thumbv7em-none-eabihf/release/my-firmware").unwrap();
+/// let report = MemoryReport::from_elf(&elf).unwrap();
+/// println!("{report}");
+/// ```
+///
+/// This is only available with the `"report"` feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MemoryReport {
+    /// The chip family that the program targets.
+    pub family: Family,
+    /// Usage of each memory region, in linker script order.
+    pub regions: Vec<RegionUsage>,
+    /// Utilization of FlexRAM banks, for each kind of allocated bank.
+    pub flexram: Vec<FlexRamUsage>,
+}
+
+/// Memory usage of a single memory region.
+///
+/// `used`, `padding`, and `free` sum to `length`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RegionUsage {
+    /// The memory region.
+    pub memory: Memory,
+    /// The region's starting address.
+    pub origin: u32,
+    /// The region's size, in bytes.
+    pub length: u32,
+    /// Bytes occupied by sections.
+    pub used: u32,
+    /// Bytes lost to alignment between, and in front of, sections.
+    pub padding: u32,
+    /// Bytes behind the last section.
+    pub free: u32,
+    /// The sections in this region, sorted by address.
+    pub sections: Vec<SectionUsage>,
+}
+
+/// A section within a memory region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SectionUsage {
+    /// The name of the section.
+    pub name: String,
+    /// The section's address in this region.
+    pub address: u32,
+    /// The section's size, in bytes.
+    pub size: u32,
+    /// Bytes lost to alignment in front of this section.
+    ///
+    /// For example, this includes the padding that aligns the vector table
+    /// to 1024 bytes.
+    pub padding: u32,
+    /// Is this the load image of a section that's copied into RAM?
+    pub loaded: bool,
+}
+
+/// Utilization of FlexRAM banks of a single kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FlexRamUsage {
+    /// The kind of bank.
+    pub kind: FlexRamKind,
+    /// How many banks are allocated to this kind.
+    pub banks: usize,
+    /// The size of each bank, in bytes.
+    pub bank_size: usize,
+    /// How many banks hold at least one byte of a section.
+    ///
+    /// This is `None` for OCRAM, since the OCRAM region may combine FlexRAM
+    /// banks with dedicated OCRAM.
+    pub used_banks: Option<usize>,
+}
+
+/// An allocated section, read from the ELF file.
+struct ElfSection {
+    name: String,
+    vma: u32,
+    lma: Option<u32>,
+    size: u32,
+}
+
+impl MemoryReport {
+    /// Produce a memory report from the contents of an ELF file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contents aren't an ELF file, or if the program
+    /// wasn't linked with a linker script from this version of the runtime.
+    pub fn from_elf(contents: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let elf = Elf::parse(contents)?;
+        let symbol = |name: &str| {
+            elf.syms
+                .iter()
+                .find(|sym| elf.strtab.get_at(sym.st_name) == Some(name))
+                .map(|sym| sym.st_value as u32)
+        };
+
+        let family = symbol("__imxrt_rt_v0.2")
+            .and_then(Family::from_id)
+            .ok_or("The program isn't linked with imxrt-rt 0.2")?;
+        let flexram_config =
+            symbol("__flexram_config").ok_or("The program has no FlexRAM configuration")?;
+        let layout = flexram_layout_from_config(family, flexram_config);
+
+        let sections: Vec<ElfSection> = elf
+            .section_headers
+            .iter()
+            .filter(|sec| sec.sh_flags & SHF_ALLOC as u64 != 0 && sec.sh_size > 0)
+            .map(|sec| {
+                let lma = if sec.sh_type == SHT_NOBITS {
+                    None
+                } else {
+                    elf.program_headers
+                        .iter()
+                        .filter(|phdr| phdr.p_type == PT_LOAD)
+                        .find(|phdr| {
+                            sec.sh_offset >= phdr.p_offset
+                                && sec.sh_offset - phdr.p_offset < phdr.p_filesz
+                        })
+                        .map(|phdr| (phdr.p_paddr + sec.sh_offset - phdr.p_offset) as u32)
+                };
+                ElfSection {
+                    name: elf.shdr_strtab.get_at(sec.sh_name).unwrap_or("?").into(),
+                    vma: sec.sh_addr as u32,
+                    lma,
+                    size: sec.sh_size as u32,
+                }
+            })
+            .collect();
+
+        let mut regions = Vec::new();
+        for memory in [Memory::Flash, Memory::Itcm, Memory::Dtcm, Memory::Ocram] {
+            let (Some(origin), Some(length)) = (
+                symbol(&format!("__imxrt_rt_{memory}_origin")),
+                symbol(&format!("__imxrt_rt_{memory}_length")),
+            ) else {
+                continue;
+            };
+            let contains =
+                |address: u32| (origin..origin.saturating_add(length)).contains(&address);
+            let in_region = sections
+                .iter()
+                .flat_map(|sec| {
+                    let at_vma = contains(sec.vma).then_some((sec.vma, false));
+                    let at_lma = sec
+                        .lma
+                        .filter(|lma| *lma != sec.vma && contains(*lma))
+                        .map(|lma| (lma, true));
+                    at_vma
+                        .into_iter()
+                        .chain(at_lma)
+                        .map(|(address, loaded)| SectionUsage {
+                            name: sec.name.clone(),
+                            address,
+                            size: sec.size,
+                            padding: 0,
+                            loaded,
+                        })
+                })
+                .collect();
+            regions.push(RegionUsage::new(memory, origin, length, in_region));
+        }
+
+        let bank_size = family.flexram_bank_size();
+        let flexram = [FlexRamKind::Itcm, FlexRamKind::Dtcm, FlexRamKind::Ocram]
+            .into_iter()
+            .filter_map(|kind| {
+                let banks = layout_count_of(kind, &layout);
+                if banks == 0 {
+                    return None;
+                }
+                let memory = match kind {
+                    FlexRamKind::Itcm => Some(Memory::Itcm),
+                    FlexRamKind::Dtcm => Some(Memory::Dtcm),
+                    FlexRamKind::Ocram | FlexRamKind::Unused => None,
+                };
+                let used_banks = memory
+                    .and_then(|memory| regions.iter().find(|region| region.memory == memory))
+                    .map(|region| {
+                        // Account for any space that's reserved in front of the region.
+                        let reserved = (banks * bank_size).saturating_sub(region.length as usize);
+                        let extent = (region.length - region.free) as usize + reserved;
+                        extent.div_ceil(bank_size)
+                    });
+                Some(FlexRamUsage {
+                    kind,
+                    banks,
+                    bank_size,
+                    used_banks,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            family,
+            regions,
+            flexram,
+        })
+    }
+
+    /// Returns the RAM that this program occupies.
+    ///
+    /// Use this with [`Family::fit_flexram_banks`] to find the fewest FlexRAM
+    /// banks for the same program. Each requirement spans from the start of the
+    /// region to the end of its last section. Note that a stack or heap that
+    /// [consumes the remainder](crate::RuntimeBuilder#remainder-sizing) of its
+    /// region occupies the whole region.
+    pub fn ram_requirements(&self) -> RamRequirements {
+        let occupied = |memory| {
+            self.regions
+                .iter()
+                .find(|region| region.memory == memory)
+                .map_or(0, |region| (region.length - region.free) as usize)
+        };
+        RamRequirements {
+            itcm: occupied(Memory::Itcm),
+            dtcm: occupied(Memory::Dtcm),
+            ocram: occupied(Memory::Ocram),
+        }
+    }
+
+    /// Render the report as a JSON object.
+    ///
+    /// The object's keys match the field names of this report.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        write!(json, "{{\"family\":\"{:?}\",\"regions\":[", self.family).unwrap();
+        for (idx, region) in self.regions.iter().enumerate() {
+            if idx > 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                "{{\"memory\":\"{}\",\"origin\":{},\"length\":{},\"used\":{},\"padding\":{},\"free\":{},\"sections\":[",
+                region.memory, region.origin, region.length, region.used, region.padding, region.free
+            )
+            .unwrap();
+            for (idx, section) in region.sections.iter().enumerate() {
+                if idx > 0 {
+                    json.push(',');
+                }
+                write!(
+                    json,
+                    "{{\"name\":{},\"address\":{},\"size\":{},\"padding\":{},\"loaded\":{}}}",
+                    json_string(&section.name),
+                    section.address,
+                    section.size,
+                    section.padding,
+                    section.loaded
+                )
+                .unwrap();
+            }
+            json.push_str("]}");
+        }
+        json.push_str("],\"flexram\":[");
+        for (idx, usage) in self.flexram.iter().enumerate() {
+            if idx > 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                "{{\"kind\":\"{}\",\"banks\":{},\"bank_size\":{},\"used_banks\":",
+                kind_name(usage.kind),
+                usage.banks,
+                usage.bank_size
+            )
+            .unwrap();
+            match usage.used_banks {
+                Some(used_banks) => write!(json, "{used_banks}}}").unwrap(),
+                None => json.push_str("null}"),
+            }
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+impl RegionUsage {
+    /// Compute the usage of a region from its sections.
+    ///
+    /// The padding of each section is computed here.
+    fn new(memory: Memory, origin: u32, length: u32, mut sections: Vec<SectionUsage>) -> Self {
+        sections.sort_by_key(|section| section.address);
+        let mut cursor = origin;
+        let mut used = 0;
+        let mut padding = 0;
+        for section in &mut sections {
+            section.padding = section.address.saturating_sub(cursor);
+            padding += section.padding;
+            used += section.size;
+            cursor = cursor.max(section.address + section.size);
+        }
+        Self {
+            memory,
+            origin,
+            length,
+            used,
+            padding,
+            free: (origin + length).saturating_sub(cursor),
+            sections,
+        }
+    }
+}
+
+impl Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Memory usage for {:?}", self.family)?;
+        for region in &self.regions {
+            let percent = if region.length > 0 {
+                100.0 * (region.length - region.free) as f64 / region.length as f64
+            } else {
+                0.0
+            };
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{} ({:#010X}, {} bytes): {} used, {} padding, {} free ({percent:.1}% full)",
+                region.memory,
+                region.origin,
+                region.length,
+                region.used,
+                region.padding,
+                region.free
+            )?;
+            writeln!(
+                f,
+                "  {:<24} {:>10} {:>10} {:>8}",
+                "Section", "Address", "Size", "Padding"
+            )?;
+            for section in &region.sections {
+                writeln!(
+                    f,
+                    "  {:<24} {:#010X} {:>10} {:>8}{}",
+                    section.name,
+                    section.address,
+                    section.size,
+                    section.padding,
+                    if section.loaded { "  (load)" } else { "" }
+                )?;
+            }
+        }
+        if let Some(usage) = self.flexram.first() {
+            writeln!(f)?;
+            writeln!(f, "FlexRAM banks ({} bytes each)", usage.bank_size)?;
+        }
+        for usage in &self.flexram {
+            write!(
+                f,
+                "  {:<6} {} allocated",
+                kind_name(usage.kind),
+                usage.banks
+            )?;
+            if let Some(used_banks) = usage.used_banks {
+                write!(f, ", {used_banks} used")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Quote and escape a string for JSON.
+fn json_string(string: &str) -> String {
+    let mut json = String::from('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn kind_name(kind: FlexRamKind) -> &'static str {
+    match kind {
+        FlexRamKind::Unused => "UNUSED",
+        FlexRamKind::Ocram => "OCRAM",
+        FlexRamKind::Dtcm => "DTCM",
+        FlexRamKind::Itcm => "ITCM",
+    }
+}
+
+/// Recover the FlexRAM layout from its configuration.
+///
+/// This is the inverse of `flexram_config`.
+fn flexram_layout_from_config(family: Family, config: u32) -> Vec<FlexRamKind> {
+    if family == Family::Imxrt1180 {
+        return match config {
+            0b10 => vec![FlexRamKind::Itcm, FlexRamKind::Itcm],
+            0b01 => vec![FlexRamKind::Dtcm, FlexRamKind::Dtcm],
+            _ => vec![FlexRamKind::Itcm, FlexRamKind::Dtcm],
+        };
+    }
+    (0..family.flexram_bank_count())
+        .map(|bank| match (config >> (2 * bank)) & 0b11 {
+            1 => FlexRamKind::Ocram,
+            2 => FlexRamKind::Dtcm,
+            3 => FlexRamKind::Itcm,
+            _ => FlexRamKind::Unused,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Family, FlexRamKind, Memory, RegionUsage, SectionUsage, flexram_layout_from_config,
+    };
+
+    fn section(name: &str, address: u32, size: u32) -> SectionUsage {
+        SectionUsage {
+            name: name.into(),
+            address,
+            size,
+            padding: 0,
+            loaded: false,
+        }
+    }
+
+    #[test]
+    fn region_usage_padding() {
+        let region = RegionUsage::new(
+            Memory::Dtcm,
+            0x2000_0000,
+            0x8000,
+            vec![
+                section(".vector_table", 0x2000_0400, 0x2B8),
+                section(".stack", 0x2000_0000, 0x100),
+                section(".heap", 0x2000_06B8, 0x48),
+            ],
+        );
+        let names: Vec<&str> = region.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, [".stack", ".vector_table", ".heap"]);
+        assert_eq!(region.sections[1].padding, 0x300);
+        assert_eq!(region.used, 0x100 + 0x2B8 + 0x48);
+        assert_eq!(region.padding, 0x300);
+        assert_eq!(region.free, 0x8000 - 0x700);
+        assert_eq!(region.used + region.padding + region.free, region.length);
+    }
+
+    #[test]
+    fn flexram_layout_round_trip() {
+        for family in [Family::Imxrt1010, Family::Imxrt1060, Family::Imxrt1170] {
+            let layout = family.default_flexram_layout();
+            let config = super::super::flexram_config(family, &layout);
+            assert_eq!(flexram_layout_from_config(family, config), layout);
+        }
+        let layout = [FlexRamKind::Dtcm, FlexRamKind::Dtcm];
+        let config = super::super::flexram_config(Family::Imxrt1180, &layout);
+        assert_eq!(
+            flexram_layout_from_config(Family::Imxrt1180, config),
+            layout
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Export serial download images.
+//!
+//! A serial download image starts with a gap, followed by the image vector
+//! table and boot data. The boot data describes the start and size of the
+//! image in RAM. Exporting the image copies every load image into a binary
+//! that starts at the load address.
+
+use std::error::Error;
+
+use super::elf;
+
+/// The offset of the image vector table from the start of the image.
+const IVT_OFFSET: u32 = 0x400;
+
+/// A program that the boot ROM's serial downloader can load.
+///
+/// Use [`serial_download_image`] to export the image from a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerialDownloadImage {
+    /// Where to write the binary, with `WRITE_FILE`.
+    pub load_address: u32,
+    /// The address of the image vector table, for `JUMP_ADDRESS`.
+    pub jump_address: u32,
+    /// The image.
+    pub binary: Vec<u8>,
+}
+
+/// Export the image that the boot ROM's serial downloader loads.
+///
+/// `contents` is an ELF file produced from a runtime that's built for
+/// [serial download](crate::RuntimeBuilder#serial-download-images). Write
+/// the returned binary to the load address, then jump to the image vector table.
+///
+/// ```no_run
+/// let elf = std::fs::read("target/thumbv7em-none-eabihf/release/my-firmware").unwrap();
+/// let image = imxrt_rt::serial_download_image(&elf).unwrap();
+/// std::fs::write("my-firmware.bin", &image.binary).unwrap();
+/// println!("WRITE_FILE {:#010X}", image.load_address);
+/// println!("JUMP_ADDRESS {:#010X}", image.jump_address);
+/// ```
+///
+/// This is only available with the `"sdp"` feature.
+///
+/// # Errors
+///
+/// Returns an error if the contents aren't a 32-bit, little-endian ELF file, or
+/// if the program isn't a serial download image. Returns an error if the program
+/// loads anything outside of its image.
+pub fn serial_download_image(contents: &[u8]) -> Result<SerialDownloadImage, Box<dyn Error>> {
+    let elf = elf::parse(contents)?;
+    let not_sdp = "The program isn't a serial download image";
+    let ivt = elf::symbol(&elf, "__ivt").ok_or(not_sdp)?;
+    let boot_data = elf::symbol(&elf, "__boot_data").ok_or(not_sdp)?;
+
+    let segments = elf::load_segments(&elf, contents)?;
+    let boot_data_offset = segments
+        .iter()
+        .filter(|segment| {
+            boot_data
+                .checked_add(8)
+                .is_some_and(|end| end <= segment.paddr_end())
+        })
+        .find_map(|segment| segment.file_offset(boot_data))
+        .ok_or("The boot data isn't loaded")?;
+
+    let start = elf::read_u32(contents, boot_data_offset)?;
+    let size = elf::read_u32(contents, boot_data_offset + 4)?;
+    if start.checked_add(IVT_OFFSET) != Some(ivt) {
+        return Err(not_sdp.into());
+    }
+    let end = start
+        .checked_add(size)
+        .ok_or("The boot data describes an invalid image")?;
+
+    let mut binary = vec![0; size as usize];
+    for segment in &segments {
+        if segment.paddr < start || segment.paddr_end() > end {
+            return Err(format!(
+                "The program loads {:#010X}..{:#010X}, outside of its image",
+                segment.paddr,
+                segment.paddr_end()
+            )
+            .into());
+        }
+        let at = (segment.paddr - start) as usize;
+        binary[at..at + segment.filesz as usize].copy_from_slice(segment.contents(contents));
+    }
+
+    Ok(SerialDownloadImage {
+        load_address: start,
+        jump_address: ivt,
+        binary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::serial_download_image;
+    use crate::host::elf::builder::ElfBuilder;
+
+    const IMAGE: u32 = 0x2020_0000;
+    const IVT: u32 = IMAGE + 0x400;
+    const TEXT: u32 = IMAGE + 0x800;
+
+    /// The image vector table and boot data.
+    fn boot_header() -> Vec<u8> {
+        let mut header = Vec::new();
+        for word in [
+            0x402000D1,
+            TEXT,
+            0,
+            0,
+            IVT + 32,
+            IVT,
+            0,
+            0,
+            IMAGE,
+            TEXT + 16 - IMAGE,
+            0,
+            0xDEADBEEF,
+        ] {
+            header.extend_from_slice(&u32::to_le_bytes(word));
+        }
+        header
+    }
+
+    /// Build a program with a boot header, and some instructions behind a gap.
+    fn program(ivt: u32) -> Vec<u8> {
+        let text: Vec<u8> = (0..16).map(|byte| byte * 3).collect();
+        ElfBuilder::default()
+            .segment(IVT, IVT, &boot_header())
+            .segment(TEXT, TEXT, &text)
+            .symbol("__ivt", ivt)
+            .symbol("__boot_data", IVT + 32)
+            .build()
+    }
+
+    #[test]
+    fn export_program() {
+        let image = serial_download_image(&program(IVT)).unwrap();
+        assert_eq!(image.load_address, IMAGE);
+        assert_eq!(image.jump_address, IVT);
+        assert_eq!(image.binary.len(), (TEXT + 16 - IMAGE) as usize);
+        assert!(image.binary[..0x400].iter().all(|&byte| byte == 0));
+        assert_eq!(&image.binary[0x400..0x430], &boot_header());
+        assert_eq!(
+            &image.binary[0x800..],
+            &(0..16).map(|b| b * 3).collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn not_a_serial_download_image() {
+        // A flash image's IVT isn't 1 KiB from the start of the image.
+        assert!(serial_download_image(&program(IMAGE + 0x1000)).is_err());
+        assert!(serial_download_image(b"not an ELF file").is_err());
+    }
+
+    #[test]
+    fn malformed_boot_data() {
+        // The boot data's image size isn't loaded.
+        let contents = ElfBuilder::default()
+            .segment(IVT, IVT, &boot_header()[..36])
+            .symbol("__ivt", IVT)
+            .symbol("__boot_data", IVT + 32)
+            .build();
+        assert!(serial_download_image(&contents).is_err());
+
+        // The boot data's image size doesn't cover the image.
+        let mut header = boot_header();
+        header[36..40].copy_from_slice(&0x800u32.to_le_bytes());
+        let contents = ElfBuilder::default()
+            .segment(IVT, IVT, &header)
+            .segment(TEXT, TEXT, &[0; 16])
+            .symbol("__ivt", IVT)
+            .symbol("__boot_data", IVT + 32)
+            .build();
+        assert!(serial_download_image(&contents).is_err());
+    }
+}
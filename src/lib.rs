@@ -137,6 +137,13 @@
 //! features = ["device"] # ... and here
 //! ```
 //!
+//! The `"report"`, `"compress"`, `"checksum"`, and `"sdp"` features are exceptions. They only
+//! affect the host. `"report"` enables the [memory usage report](#memory-usage-reports),
+//! `"compress"` enables the [compression of load images](RuntimeBuilder#compressed-load-images),
+//! `"checksum"` adds the CRC for [image checks](RuntimeBuilder#image-checks), and `"sdp"`
+//! exports [serial download images](RuntimeBuilder#serial-download-images).
+//! You don't need these features in your firmware's `[dependencies]`.
+//!
 //! # Limitations
 //!
 //! By default, the crate considers the assignment of FlexRAM memory banks to
@@ -148,8 +155,8 @@
 //! domain assignment (according to AN12077).
 //!
 //! The runtime installs a `cortex-m-rt` `pre_init` function to configure the runtime.
-//! You cannot also define a `pre_init` function, and this crate does not support any
-//! other mechanism for running code before `main()`.
+//! You cannot also define a `pre_init` function. Instead, use the runtime's
+//! [user pre-init function](#running-code-before-main).
 //!
 //! The implementation assumes all flash is FlexSPI.
 //!
@@ -195,6 +202,71 @@
 //!
 //! Without this reservation, this program is allowed to use the lower addresses of
 //! OCRAM without concern for the boot ROM.
+//!
+//! ## Placing items in a memory
+//!
+//! The `itcm`, `dtcm`, `ocram`, and `flash` attributes place a single function or
+//! static into a memory, regardless of where the runtime places its section.
+//!
+//! ```ignore
+//! #[imxrt_rt::itcm]
+//! fn filter_block(samples: &mut [i32]) {
+//!     // Executes from ITCM...
+//! }
+//!
+//! #[imxrt_rt::dtcm]
+//! static mut SAMPLES: [i32; 256] = [0; 256];
+//!
+//! #[imxrt_rt::flash]
+//! static LOOKUP: [u16; 1024] = [0; 1024];
+//! ```
+//!
+//! The runtime copies items placed in ITCM, DTCM, and OCRAM before `main()`. Zero-initialized
+//! statics still occupy space in the image. Placed functions are never inlined, unless you
+//! add your own `#[inline]` attribute. In a RAM image, items placed in flash are placed with
+//! the image's instructions.
+//!
+//! Flash can't be written. You can't place a `static mut` in flash, or a static that has
+//! interior mutability, like an atomic or a type that holds an `UnsafeCell`. These statics
+//! fail to compile with an error about interior mutable borrows.
+//!
+//! Linking fails if you place an item into a memory that doesn't exist. The attributes
+//! are only available when building for the target.
+//!
+//! ## Running code before `main()`
+//!
+//! The `user_pre_init` attribute marks a function that the runtime calls before it
+//! initializes `.data` and `.bss`. Use this function to configure clocks or external
+//! RAM before the runtime initializes statics.
+//!
+//! ```ignore
+//! #[imxrt_rt::user_pre_init]
+//! unsafe fn configure_sdram() {
+//!     // Runs before statics are initialized...
+//! }
+//! ```
+//!
+//! The runtime calls the function after it configures FlexRAM, zeroes ECC RAM, and
+//! copies sections into RAM, so the function may execute from any memory. Since statics
+//! aren't initialized, the function must not read or write statics; the compiler can't
+//! check this for you. The runtime enables the FPU before it calls the function, so the
+//! function may use floating point. A program may have at most one user pre-init function.
+//! It's exported with the symbol name `__imxrt_rt_user_pre_init`.
+//!
+//! ## Memory usage reports
+//!
+//! With the `"report"` feature, the host API includes `MemoryReport`. A memory report
+//! reads a program that's linked with this runtime, and it describes the used and free
+//! bytes of each memory region and section. It also shows the padding between sections,
+//! like the padding that aligns the vector table, and how many FlexRAM banks hold data.
+//! Reports can be printed for humans, or rendered as JSON for tools.
+//!
+//! The `imxrt-rt-report` program prints the report for an ELF file.
+//!
+//! ```text
+//! cargo install imxrt-rt --features report
+//! imxrt-rt-report [--json] path/to/firmware.elf
+//! ```
 
 #![cfg_attr(all(target_arch = "arm", target_os = "none"), no_std)]
 
@@ -1,8 +1,12 @@
 //! i.MX RT target support.
 //!
 //! Defines a `cortex-m-rt` pre-init function that disables watchdogs and initializes TCM.
-//! It then copies instructions, read-only data, and the vector table to their intended location.
-//! This only happens if LMAs and VMAs differ.
+//! If the image is checked, it computes the CRC-32 of the flash image, and it handles a mismatch
+//! before copying anything from flash. It then copies instructions, read-only data, and the
+//! vector table to their intended location, along with any sections in the copy table. This only
+//! happens if LMAs and VMAs differ. Sections in the copy table may be LZ4 compressed; those sections
+//! are decompressed instead of copied. Finally, it calls the user's pre-init function, if there is one,
+//! and it runs static constructors, if enabled.
 //!
 //! There's a few behaviors worth mentioning:
 //!
@@ -27,12 +31,19 @@
 //!
 //! <https://community.nxp.com/t5/i-MX-RT/RT1176-ROM-code-does-not-set-stack-pointer-correctly/td-p/1388830>
 
-use core::{arch::global_asm, ffi::c_void};
+use core::{
+    arch::{asm, global_asm},
+    ffi::c_void,
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 pub use cortex_m_rt::*;
+pub use imxrt_rt_macros::{dtcm, flash, itcm, ocram, user_pre_init};
 
 global_asm! {r#"
 .cfi_sections .debug_frame
+.fpu fpv4-sp-d16
 .section .__pre_init,"ax"
 .global __pre_init
 .type __pre_init,%function
@@ -41,37 +52,85 @@ global_asm! {r#"
 
 .macro copy_section dst, src, end
     ldr r0, =\dst
-    ldr r2, =\src
-    cmp r2, r0
-    beq 999f
-
     ldr r1, =\end
-    888:
-    cmp r1, r0
-    beq 999f
-    ldm r2!, {{r3}}
-    stm r0!, {{r3}}
-    b 888b
-    999:
+    ldr r2, =\src
+    bl __imxrt_rt_copy
 .endm
 
 .macro zero_section64 beg, end
     ldr r0, =\beg
     ldr r1, =\end
-    movw r2, #0
-
-  888:
-    cmp r0, r1
-    bge 999f
+    bl __imxrt_rt_zero
+.endm
 
-    strd r2, r2, [r0], #8
-    b 888b
+# Boot timestamps are recorded if the host sets __boot_timestamps. Until
+# the stack is usable, a timestamp is held in an FPU register.
+.macro stash_timestamp sreg
+    ldr r0, =__boot_timestamps
+    cbz r0, 2001f
+    ldr r0, =0xE0001004             @ DWT_CYCCNT
+    ldr r0, [r0]
+    vmov \sreg, r0
+    2001:
+.endm
 
-  999:
+# Once the stack is usable, a timestamp is stored in the record.
+.macro timestamp offset
+    ldr r0, =__boot_timestamps
+    cbz r0, 2002f
+    ldr r0, =0xE0001004             @ DWT_CYCCNT
+    ldr r0, [r0]
+    ldr r1, =__imxrt_rt_boot_timestamps
+    str r0, [r1, #\offset]
+    2002:
+.endm
 
+# Continue the CRC-32 in r2 over the bytes from r0 until r1. r9 is the
+# CRC table. Clobbers r0 and r3.
+.macro crc32_bytes
+    3100:
+    cmp r0, r1
+    bhs 3101f
+    ldrb r3, [r0], #1
+    eors r3, r3, r2
+    uxtb r3, r3
+    ldr r3, [r9, r3, lsl #2]
+    eor r2, r3, r2, lsr #8
+    b 3100b
+    3101:
 .endm
 
 __pre_init:
+    # The stack isn't usable until FlexRAM is configured and ECC RAM is
+    # zeroed. Until then, r12 holds the return address.
+    mov r12, lr
+
+    # Start the cycle counter for boot timestamps. Enable the FPU, since
+    # its registers hold timestamps until the stack is usable. s2 holds
+    # the FPU access that's restored later.
+    ldr r0, =__boot_timestamps
+    cbz r0, 2000f
+    ldr r0, =0xE000ED88             @ CPACR
+    ldr r1, [r0]
+    orr r2, r1, #0xF<<20            @ Full access to CP10 and CP11
+    str r2, [r0]
+    dsb
+    isb
+    vmov s2, r1
+    ldr r0, =0xE000EDFC             @ DEMCR
+    ldr r1, [r0]
+    orr r1, r1, #1<<24              @ r1 |= TRCENA
+    str r1, [r0]
+    ldr r0, =0xE0001000             @ DWT_CTRL
+    ldr r1, =0xC5ACCE55             @ Unlock the DWT on the Cortex-M7
+    str r1, [r0, #0xFB0]            @ DWT_LAR = 0xC5ACCE55
+    movs r1, #0
+    str r1, [r0, #4]                @ DWT_CYCCNT = 0
+    ldr r1, [r0]
+    orr r1, r1, #1                  @ r1 |= CYCCNTENA
+    str r1, [r0]
+    2000:
+
     ldr r0, =__imxrt_rt_v0.2        @ Need to know which chip family we're initializing.
     ldr r1, =0x1180
     cmp r0, r1                      @ Is this an 1180?
@@ -88,6 +147,7 @@ __pre_init:
     ldr r3, [r2]                    @ r3 = RTWDOG[CS]
     bic r3, r3, #1<<7               @ r3 = r3 & !(1 << 7), clears enable.
     str r3, [r2]                    @ RTWDOG[CS] = r3
+    stash_timestamp s0
 
     # Prepare FlexRAM regions.
     ldr r0, =0x400AC000             @ IMXRT_IOMUXC_GPR base address for 10xx chips, overwritten if actually 11xx...
@@ -101,6 +161,7 @@ __pre_init:
     ldr r1, [r0, #64]               @ r1 = *(IMXRT_IOMUXC_GPR + 16)
     orr r1, r1, #1<<2               @ r1 |= 1 << 2
     str r1, [r0, #64]               @ *(IMXRT_IOMUXC_GPR + 16) = r1
+    stash_timestamp s1
 
     # If there's an ITCM ECC region, make sure
     #
@@ -137,23 +198,339 @@ __pre_init:
     zero_section64 __sdtcm  , __edtcm
     zero_section64 __socram , __eocram
 
+    # If the crash log is in ECC RAM, the span of its memory ends at the
+    # log, and the tail continues behind it. The log keeps its contents
+    # unless the chip powered on, since its ECC is only valid after that
+    # first zeroing. Only the 1160 and 1170 have ECC RAM.
+    zero_section64 __secc_tail , __eecc_tail
+    ldr r0, =__eecc_crashlog
+    cbz r0, 1001f
+    ldr r0, =0x40C04010             @ SRC_SRSR for 11xx chips
+    ldr r0, [r0]
+    tst r0, #1                      @ Power-on reset?
+    beq 1001f
+    zero_section64 __secc_crashlog , __eecc_crashlog
+    1001:
+
     b 1000f
 
     1180:
+    stash_timestamp s0              @ There's no watchdog to disable.
     ldr r0, =0x444F0060             @ M33_CONFIG
     ldr r1, =__flexram_config
     str r1, [r0, #0]
+    stash_timestamp s1
 
     1000:
+    mov lr, r12
+    push {{r3-r11, lr}}             @ r3 keeps the stack 8 byte aligned for calls.
+
+    # A position-independent image finds its load offset, the distance from
+    # where it's linked to where it runs. The offset is kept on the stack. It's
+    # zero for all other images.
+    sub sp, sp, #8
+    ldr r0, =__position_independent
+    cbz r0, 7000f
+    adr r0, 7000f
+    ldr r1, =7000f
+    subs r0, r0, r1
+    .p2align 2
+    7000:
+    str r0, [sp]
+
+    # Move the stashed timestamps into the record, then restore the FPU.
+    ldr r0, =__boot_timestamps
+    cbz r0, 2003f
+    ldr r0, =__imxrt_rt_boot_timestamps
+    vstm r0, {{s0-s1}}
+    mrs r0, control
+    bic r0, r0, #1<<2               @ Clear FPCA, set by the FPU instructions.
+    msr control, r0
+    vmov r1, s2
+    ldr r0, =0xE000ED88             @ CPACR
+    str r1, [r0]
+    dsb
+    isb
+    2003:
+
+    # If the host sets __reset_reason, snapshot the reset status, then clear
+    # it, so that the next snapshot only describes the next reset. The status
+    # bits are write 1 to clear. The host rejects this for the 1180.
+    ldr r0, =__reset_reason
+    cbz r0, 6000f
+    ldr r0, =__imxrt_rt_v0.2
+    ldr r1, =0x1100
+    cmp r0, r1
+    ite gt
+    ldrgt r0, =0x40C04010           @ SRC_SRSR for 11xx chips...
+    ldrle r0, =0x400F8008           @ SRC_SRSR for 10xx chips...
+    ldr r2, [r0]
+    it gt
+    uxthgt r2, r2                   @ The upper half describes the Cortex-M4.
+    str r2, [r0]
+    ldr r0, =__imxrt_rt_reset_status
+    str r2, [r0]
+    6000:
+    timestamp 8                     @ ecc
+
+    # Check the image's CRC-32 before copying anything from flash. The
+    # record holds the failure action, the start and end of the checked
+    # image, the expected CRC, and the CRC table. The record itself
+    # isn't checked.
+    ldr r4, =__imxrt_rt_image_check
+    cmp r4, #0
+    beq 3000f
+    ldm r4, {{r5-r8}}               @ r5 = action, r6 = start, r7 = end, r8 = CRC
+    add r9, r4, #16                 @ r9 = CRC table
+    mvn r2, #0
+    mov r0, r6
+    mov r1, r4
+    crc32_bytes
+    addw r0, r4, #16 + 4 * 256
+    mov r1, r7
+    crc32_bytes
+    mvn r2, r2
+    cmp r2, r8
+    beq 3000f
+    cmp r5, #1
+    bhi 3002f                       @ Call the handler...
+    beq 3001f                       @ ...or reset...
+    b .                             @ ...or spin.
+    3001:
+    ldr r0, =0xE000ED0C             @ AIRCR
+    ldr r1, =0x05FA0004             @ VECTKEY | SYSRESETREQ
+    dsb
+    str r1, [r0]
+    dsb
+    b .
+    3002:
+    blx r5
+    b .
+    3000:
+    timestamp 12                    @ image_check
+
     copy_section __stext            , __sitext          , __etext
+    timestamp 16                    @ text
     copy_section __svector_table    , __sivector_table  , __evector_table
+    timestamp 20                    @ vector_table
     copy_section __srodata          , __sirodata        , __erodata
+    timestamp 24                    @ rodata
+
+    # Copy the sections described by the copy table. Each record
+    # is the destination start, destination end, and source. If
+    # bit 0 of the source is set, the source is an LZ4 block that
+    # decompresses into the destination.
+    #
+    # The table, and every source, is in flash. They move with the load
+    # offset.
+    ldr r12, =__scopy_table
+    ldr r3, [sp]
+    add r12, r12, r3
+    777:
+    ldr r3, =__ecopy_table
+    ldr r4, [sp]
+    add r3, r3, r4
+    cmp r12, r3
+    beq 666f
+    ldm r12!, {{r0, r1, r2}}        @ r0 = dst, r1 = end, r2 = src
+    add r2, r2, r4
+    tst r2, #1
+    bne 444f
+    bl __imxrt_rt_copy
+    b 777b
+
+    444:
+    bic r2, r2, #1
+    440:                            @ Each sequence starts with a token.
+    ldrb r3, [r2], #1               @ r3 = token
+    lsr r4, r3, #4                  @ r4 = literal length
+    cmp r4, #15
+    bne 442f
+    441:
+    ldrb r5, [r2], #1
+    add r4, r4, r5
+    cmp r5, #255
+    beq 441b
+    442:                            @ Copy the literals.
+    cbz r4, 443f
+    ldrb r5, [r2], #1
+    strb r5, [r0], #1
+    subs r4, r4, #1
+    b 442b
+    443:
+    cmp r0, r1                      @ The last sequence only has literals.
+    bhs 777b
+    ldrb r4, [r2], #1
+    ldrb r5, [r2], #1
+    orr r4, r4, r5, lsl #8          @ r4 = match offset
+    sub r6, r0, r4                  @ r6 = match source
+    and r3, r3, #15                 @ r3 = match length - 4
+    cmp r3, #15
+    bne 446f
+    445:
+    ldrb r5, [r2], #1
+    add r3, r3, r5
+    cmp r5, #255
+    beq 445b
+    446:
+    add r3, r3, #4
+    447:                            @ Copy the match. It may overlap the destination.
+    ldrb r5, [r6], #1
+    strb r5, [r0], #1
+    subs r3, r3, #1
+    bne 447b
+    b 440b
+    666:
+
+    # Entries in the global offset table that point into flash move with
+    # the load offset.
+    ldr r3, [sp]
+    cbz r3, 7002f
+    ldr r0, =__imxrt_rt_sgot
+    ldr r1, =__imxrt_rt_egot
+    ldr r4, =__imxrt_rt_sflash
+    ldr r5, =__imxrt_rt_eflash
+    sub r5, r5, r4                  @ r5 = length of flash
+    7001:
+    cmp r0, r1
+    bhs 7002f
+    ldr r2, [r0]
+    subs r6, r2, r4                 @ r6 = offset into flash, if the entry is in flash
+    cmp r6, r5
+    it lo
+    addlo r2, r2, r3
+    str r2, [r0], #4
+    b 7001b
+    7002:
+    timestamp 28                    @ copy_table
+
+    # The user's pre-init function and static constructors may use the FPU,
+    # but cortex-m-rt only enables the FPU after pre-init returns. Enable it
+    # before calling either.
+    ldr r0, =__imxrt_rt_user_pre_init
+    ldr r1, =__init_arrays
+    orrs r0, r0, r1
+    beq 4001f
+    ldr r0, =0xE000ED88             @ CPACR
+    ldr r1, [r0]
+    orr r1, r1, #0xF<<20            @ Full access to CP10 and CP11
+    str r1, [r0]
+    dsb
+    isb
+    4001:
+
+    # Call the user's pre-init function, if there is one.
+    ldr r0, =__imxrt_rt_user_pre_init
+    cbz r0, 4000f
+    blx r0
+    4000:
+
+    # Constructors expect initialized statics, and the boot timestamp for
+    # main follows their initialization. If either is enabled, initialize
+    # .data and .bss here. The host hides both from cortex-m-rt, which would
+    # otherwise initialize them after pre-init.
+    ldr r0, =__imxrt_rt_init_statics
+    cbz r0, 5002f
+    copy_section __sdata            , __imxrt_rt_sidata , __edata
+    zero_section64 __imxrt_rt_sbss  , __ebss
+    5002:
+
+    # Run static constructors, if enabled.
+    ldr r0, =__init_arrays
+    cbz r0, 5001f
+    ldr r4, =__preinit_array_start  @ .init_array follows .preinit_array.
+    ldr r5, =__init_array_end
+    5000:
+    cmp r4, r5
+    beq 5001f
+    ldr r0, [r4], #4
+    blx r0
+    b 5000b
+    5001:
+    timestamp 32                    @ main
 
     # All done; back to the reset handler.
-    bx lr
+    add sp, sp, #8
+    pop {{r3-r11, pc}}
 
 .cfi_endproc
 .size __pre_init, . - __pre_init
+
+# Copy from r2 into r0 until r0 reaches r1. The source and destination
+# must be word aligned, but the size may be any number of bytes. Copies
+# 32 bytes per iteration, then copies the tail without looping.
+#
+# Clobbers r0 through r11.
+.global __imxrt_rt_copy
+.type __imxrt_rt_copy,%function
+.thumb_func
+__imxrt_rt_copy:
+    cmp r2, r0                      @ Nothing to copy if the LMA is the VMA.
+    beq 882f
+    subs r3, r1, r0                 @ r3 = remaining bytes
+    subs r3, r3, #32
+    blo 881f
+    880:
+    ldm r2!, {{r4-r11}}
+    stm r0!, {{r4-r11}}
+    subs r3, r3, #32
+    bhs 880b
+    881:                            @ r3 is negative, but its low five bits are the tail size.
+    lsls r3, r3, #28                @ C = 16 byte tail, N = 8 byte tail
+    itt cs
+    ldmcs r2!, {{r4-r7}}
+    stmcs r0!, {{r4-r7}}
+    itt mi
+    ldmmi r2!, {{r4, r5}}
+    stmmi r0!, {{r4, r5}}
+    lsls r3, r3, #2                 @ C = 4 byte tail, N = 2 byte tail
+    itt cs
+    ldrcs r4, [r2], #4
+    strcs r4, [r0], #4
+    itt mi
+    ldrhmi r4, [r2], #2
+    strhmi r4, [r0], #2
+    lsls r3, r3, #2                 @ C = 1 byte tail
+    itt cs
+    ldrbcs r4, [r2]
+    strbcs r4, [r0]
+    882:
+    bx lr
+.size __imxrt_rt_copy, . - __imxrt_rt_copy
+
+# Zero from r0 until r1. Both addresses must be 4 byte aligned. If they're
+# 8 byte aligned, each store is 64 bits wide, which initializes ECC RAM.
+# Zeroes 32 bytes per iteration, then zeroes the tail without looping.
+#
+# Clobbers r0 through r3.
+.global __imxrt_rt_zero
+.type __imxrt_rt_zero,%function
+.thumb_func
+__imxrt_rt_zero:
+    movs r2, #0
+    movs r3, #0
+    subs r1, r1, r0                 @ r1 = remaining bytes
+    subs r1, r1, #32
+    blo 891f
+    890:
+    strd r2, r3, [r0], #8
+    strd r2, r3, [r0], #8
+    strd r2, r3, [r0], #8
+    strd r2, r3, [r0], #8
+    subs r1, r1, #32
+    bhs 890b
+    891:                            @ r1 is negative, but its low five bits are the tail size.
+    lsls r1, r1, #28                @ C = 16 byte tail, N = 8 byte tail
+    itt cs
+    strdcs r2, r3, [r0], #8
+    strdcs r2, r3, [r0], #8
+    it mi
+    strdmi r2, r3, [r0], #8
+    lsls r1, r1, #2                 @ C = 4 byte tail
+    it cs
+    strcs r2, [r0], #4
+    bx lr
+.size __imxrt_rt_zero, . - __imxrt_rt_zero
 "#
 }
 
@@ -167,3 +544,744 @@ pub fn heap_end() -> *mut u32 {
     }
     &raw mut __eheap as _
 }
+
+/// Move the program break through the heap, for C libraries.
+///
+/// The host provides this as `_sbrk` when `RuntimeBuilder::libc_symbols` is
+/// enabled. Returns the previous break, or `(void *)-1` if the break would leave
+/// the heap. `errno` isn't set.
+#[unsafe(export_name = "__imxrt_rt_sbrk")]
+extern "C" fn sbrk(increment: isize) -> *mut c_void {
+    static BREAK: AtomicUsize = AtomicUsize::new(0);
+
+    let start = heap_start().cast::<c_void>();
+    let heap = start as usize..=heap_end() as usize;
+    // Zero until the first call, which starts the break at the start of the heap.
+    let mut current = BREAK.load(Ordering::Relaxed);
+    loop {
+        let previous = current.max(*heap.start());
+        let Some(next) = previous
+            .checked_add_signed(increment)
+            .filter(|next| heap.contains(next))
+        else {
+            return core::ptr::without_provenance_mut(usize::MAX);
+        };
+        match BREAK.compare_exchange_weak(current, next, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => return start.wrapping_byte_add(previous - *heap.start()),
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+/// Cycle counts recorded while booting.
+///
+/// Each count is the value of the DWT cycle counter at the end of a boot step.
+/// The runtime starts the counter at the top of its pre-init function, so the
+/// counts don't include time spent in the boot ROM. On the 1180, there's no
+/// watchdog to disable and no ECC RAM to zero; those steps take no time.
+/// Checking the image takes no time unless the runtime checks its image.
+///
+/// Use [`boot_timestamps`] to read the counts.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BootTimestamps {
+    /// After disabling the watchdog.
+    pub watchdog: u32,
+    /// After configuring FlexRAM.
+    pub flexram: u32,
+    /// After zeroing ECC RAM.
+    pub ecc: u32,
+    /// After checking the image's CRC-32.
+    pub image_check: u32,
+    /// After copying `.text`.
+    pub text: u32,
+    /// After copying the vector table.
+    pub vector_table: u32,
+    /// After copying `.rodata`.
+    pub rodata: u32,
+    /// After copying, or decompressing, the sections in the copy table.
+    pub copy_table: u32,
+    /// After `.data` and `.bss` are initialized, and after any static
+    /// constructors run. `cortex-m-rt` calls `main()` soon after.
+    pub main: u32,
+}
+
+/// Returns the cycle counts recorded while booting.
+///
+/// Returns `None` if the runtime isn't configured to record boot timestamps.
+/// Enable the timestamps with `RuntimeBuilder::boot_timestamps`.
+///
+/// ```no_run
+/// #[imxrt_rt::entry]
+/// fn main() -> ! {
+///     let timestamps = imxrt_rt::boot_timestamps();
+///     // ...
+///     # loop {}
+/// }
+/// ```
+pub fn boot_timestamps() -> Option<BootTimestamps> {
+    unsafe extern "C" {
+        static __imxrt_rt_boot_timestamps: BootTimestamps;
+    }
+    let enabled: u32;
+    // Safety: loads a constant from the linker script.
+    unsafe { asm!("ldr {}, =__boot_timestamps", out(reg) enabled, options(nomem, nostack)) };
+    if enabled == 0 {
+        return None;
+    }
+
+    // Safety: pre-init initialized the record, and nothing writes it afterwards.
+    Some(unsafe { (&raw const __imxrt_rt_boot_timestamps).read_volatile() })
+}
+
+/// The reason for the most recent reset.
+///
+/// Use [`reset_reason`] to read the reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResetReason {
+    /// The chip powered on.
+    PowerOn,
+    /// The supply voltage exceeded its highest permitted level.
+    Voltage,
+    /// The temperature sensor reset the chip.
+    Temperature,
+    /// The user reset pin was asserted.
+    Pin,
+    /// The security module reset the chip.
+    Security,
+    /// A watchdog timed out.
+    Watchdog,
+    /// A debugger reset the chip through JTAG.
+    Debugger,
+    /// Software requested a reset, or a CPU locked up.
+    Software,
+}
+
+/// Reasons, and the status bits that indicate them on 10xx and 11xx chips.
+///
+/// When more than one bit is set, the first reason wins.
+const RESET_REASONS: [(ResetReason, [u32; 2]); 8] = [
+    (ResetReason::PowerOn, [0x0001, 0x0001]),
+    (ResetReason::Voltage, [0x0000, 0x2000]),
+    (ResetReason::Temperature, [0x0100, 0x0400]),
+    (ResetReason::Pin, [0x0008, 0x0010]),
+    (ResetReason::Security, [0x0004, 0x0008]),
+    (ResetReason::Watchdog, [0x0090, 0x4320]),
+    (ResetReason::Debugger, [0x0060, 0x00C0]),
+    (ResetReason::Software, [0x0002, 0x1806]),
+];
+
+/// Returns the reason for the most recent reset.
+///
+/// Returns `None` if the runtime isn't configured to capture the reset status.
+/// Enable the capture with `RuntimeBuilder::reset_reason`. The runtime captures
+/// the SRC reset status before it initializes memory, then it clears the status.
+/// Also returns `None` if the status doesn't indicate a reason.
+///
+/// # Limitations
+///
+/// The 1180 isn't supported yet. Its SRC reset status differs from the 1170's,
+/// and `RuntimeBuilder::reset_reason` rejects the 1180.
+///
+/// ```no_run
+/// use imxrt_rt::ResetReason;
+///
+/// if imxrt_rt::reset_reason() == Some(ResetReason::Watchdog) {
+///     // ...
+/// }
+/// ```
+pub fn reset_reason() -> Option<ResetReason> {
+    unsafe extern "C" {
+        static __imxrt_rt_reset_status: u32;
+    }
+    let (enabled, family): (u32, u32);
+    // Safety: loads constants from the linker script.
+    unsafe {
+        asm!(
+            "ldr {}, =__reset_reason",
+            "ldr {}, =__imxrt_rt_v0.2",
+            out(reg) enabled,
+            out(reg) family,
+            options(nomem, nostack),
+        )
+    };
+    if enabled == 0 {
+        return None;
+    }
+    // Safety: pre-init initialized the status, and nothing writes it afterwards.
+    let status = unsafe { (&raw const __imxrt_rt_reset_status).read_volatile() };
+    let chip = usize::from(family > 0x1100);
+    RESET_REASONS
+        .iter()
+        .find(|(_, bits)| status & bits[chip] != 0)
+        .map(|&(reason, _)| reason)
+}
+
+/// Marks a valid crash log record. The bytes spell "CLOG."
+const CRASH_LOG_MAGIC: u32 = 0x474F_4C43;
+/// The magic number, the record length, and the record's CRC-32.
+///
+/// Keep this in sync with the host's crash log.
+const CRASH_LOG_HEADER_SIZE: usize = 12;
+
+/// Returns the crash log's region and its size, if there's a crash log.
+fn crash_log_region() -> Option<(*mut u8, usize)> {
+    unsafe extern "C" {
+        static mut __scrashlog: c_void;
+    }
+    let (start, end): (usize, usize);
+    // Safety: loads constants from the linker script.
+    unsafe {
+        asm!(
+            "ldr {}, =__scrashlog",
+            "ldr {}, =__ecrashlog",
+            out(reg) start,
+            out(reg) end,
+            options(nomem, nostack, preserves_flags),
+        )
+    };
+    let size = end - start;
+    (size != 0).then(|| ((&raw mut __scrashlog).cast(), size))
+}
+
+/// Continue a CRC-32 with one byte.
+fn crc32(crc: u32, byte: u8) -> u32 {
+    (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+        (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg())
+    })
+}
+
+/// Writes a record into the crash log.
+///
+/// The crash log is configured with `RuntimeBuilder::crash_log`. Use the writer
+/// in a panic or fault handler, then read the record with [`read_crash_log`]
+/// after reset. The record is only valid after you [`finish`](Self::finish) it.
+///
+/// ```no_run
+/// use core::fmt::Write;
+///
+/// fn log_panic(info: &core::panic::PanicInfo) {
+///     if let Some(mut log) = imxrt_rt::CrashLogWriter::new() {
+///         write!(log, "{info}").ok();
+///         log.finish();
+///     }
+/// }
+/// ```
+///
+/// If a record is larger than the crash log, the record is truncated. Only use one
+/// writer at a time.
+pub struct CrashLogWriter {
+    region: *mut u8,
+    capacity: usize,
+    length: usize,
+}
+
+impl CrashLogWriter {
+    /// Start a new record, invalidating the previous record.
+    ///
+    /// Returns `None` if the runtime doesn't have a crash log.
+    pub fn new() -> Option<Self> {
+        let (region, size) = crash_log_region()?;
+        // Safety: the region is reserved for the crash log, and it's 8-byte aligned.
+        unsafe { region.cast::<u32>().write_volatile(0) };
+        Some(Self {
+            region,
+            capacity: size - CRASH_LOG_HEADER_SIZE,
+            length: 0,
+        })
+    }
+
+    /// Append bytes to the record.
+    ///
+    /// Returns the number of bytes appended. This is less than `bytes.len()` if the
+    /// record is full.
+    pub fn write(&mut self, bytes: &[u8]) -> usize {
+        let count = bytes.len().min(self.capacity - self.length);
+        for &byte in &bytes[..count] {
+            // Safety: the offset is within the crash log.
+            unsafe {
+                self.region
+                    .add(CRASH_LOG_HEADER_SIZE + self.length)
+                    .write_volatile(byte)
+            };
+            self.length += 1;
+        }
+        count
+    }
+
+    /// Finish the record, so that it can be read after reset.
+    pub fn finish(self) {
+        let header = self.region.cast::<u32>();
+        let mut crc = !0;
+        for offset in 0..self.length {
+            // Safety: the offset is within the crash log.
+            crc = crc32(crc, unsafe {
+                self.region
+                    .add(CRASH_LOG_HEADER_SIZE + offset)
+                    .read_volatile()
+            });
+        }
+        // Safety: the header is within the crash log. The magic number is written
+        // last, after the rest of the record is complete.
+        unsafe {
+            header.add(1).write_volatile(self.length as u32);
+            header.add(2).write_volatile(!crc);
+            asm!("dmb", options(nostack, preserves_flags));
+            header.write_volatile(CRASH_LOG_MAGIC);
+            asm!("dsb", options(nostack, preserves_flags));
+        }
+    }
+}
+
+impl core::fmt::Write for CrashLogWriter {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Read the crash log record into `buffer`.
+///
+/// Returns the length of the record, or `None` if there's no valid record. If the
+/// record is larger than `buffer`, only the start of the record is copied. The record
+/// remains in the crash log until you [clear](clear_crash_log) it, or until you write
+/// another record.
+pub fn read_crash_log(buffer: &mut [u8]) -> Option<usize> {
+    let (region, size) = crash_log_region()?;
+    let header = region.cast::<u32>();
+    // Safety: the header is within the crash log.
+    let (magic, length) = unsafe {
+        (
+            header.read_volatile(),
+            header.add(1).read_volatile() as usize,
+        )
+    };
+    if magic != CRASH_LOG_MAGIC || length > size - CRASH_LOG_HEADER_SIZE {
+        return None;
+    }
+    let mut crc = !0;
+    for offset in 0..length {
+        // Safety: the length is within the crash log.
+        let byte = unsafe { region.add(CRASH_LOG_HEADER_SIZE + offset).read_volatile() };
+        if let Some(slot) = buffer.get_mut(offset) {
+            *slot = byte;
+        }
+        crc = crc32(crc, byte);
+    }
+    // Safety: the header is within the crash log.
+    (!crc == unsafe { header.add(2).read_volatile() }).then_some(length)
+}
+
+/// Invalidate the crash log record.
+///
+/// Does nothing if the runtime doesn't have a crash log.
+pub fn clear_crash_log() {
+    if let Some((region, _)) = crash_log_region() {
+        // Safety: the header is within the crash log.
+        unsafe { region.cast::<u32>().write_volatile(0) };
+    }
+}
+
+global_asm! {r#"
+# The runtime's fault handler, if enabled. It's placed near the
+# HardFaultTrampoline, which branches here with the same LR.
+.section .HardFault.imxrt_rt,"ax"
+.global __imxrt_rt_fault
+.type __imxrt_rt_fault,%function
+.thumb_func
+__imxrt_rt_fault:
+    tst lr, #4                      @ Which stack holds the exception frame?
+    ite eq
+    mrseq r0, msp
+    mrsne r0, psp
+    b __imxrt_rt_record_fault
+.size __imxrt_rt_fault, . - __imxrt_rt_fault
+"#
+}
+
+/// Marks a valid fault record. The bytes spell "FULT."
+const FAULT_RECORD_MAGIC: u32 = 0x544C_5546;
+
+/// State recorded by the runtime's fault handler.
+///
+/// The fault handler is enabled with `RuntimeBuilder::fault_handler`. Read the
+/// record with [`fault_record`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FaultRecord {
+    /// The active exception number. HardFault is 3, MemoryManagement is 4,
+    /// BusFault is 5, and UsageFault is 6.
+    pub vector: u32,
+    /// The Configurable Fault Status Register.
+    pub cfsr: u32,
+    /// The HardFault Status Register.
+    pub hfsr: u32,
+    /// The MemManage Fault Address Register.
+    pub mmfar: u32,
+    /// The BusFault Address Register.
+    pub bfar: u32,
+    /// The registers stacked on exception entry.
+    pub frame: ExceptionFrame,
+}
+
+/// The fault handler's record in RAM. Keep the size in sync with the host.
+#[repr(C)]
+struct StoredFault {
+    magic: u32,
+    record: FaultRecord,
+}
+
+/// Returns the fault handler's record, if there's a fault handler.
+fn stored_fault() -> Option<*mut StoredFault> {
+    let stored: *mut StoredFault;
+    // Safety: loads a constant from the linker script.
+    unsafe {
+        asm!(
+            "ldr {}, =__imxrt_rt_fault_record",
+            out(reg) stored,
+            options(nomem, nostack, preserves_flags),
+        )
+    };
+    (!stored.is_null()).then_some(stored)
+}
+
+/// Record the fault, then take the fault action.
+#[unsafe(export_name = "__imxrt_rt_record_fault")]
+unsafe extern "C" fn record_fault(frame: *const ExceptionFrame) -> ! {
+    const SCB_CFSR: *const u32 = core::ptr::without_provenance(0xE000_ED28);
+    const SCB_HFSR: *const u32 = core::ptr::without_provenance(0xE000_ED2C);
+    const SCB_MMFAR: *const u32 = core::ptr::without_provenance(0xE000_ED34);
+    const SCB_BFAR: *const u32 = core::ptr::without_provenance(0xE000_ED38);
+    const SCB_DCCMVAC: *mut u32 = core::ptr::without_provenance_mut(0xE000_EF68);
+    const SCB_AIRCR: *mut u32 = core::ptr::without_provenance_mut(0xE000_ED0C);
+
+    let ipsr: u32;
+    // Safety: reads the active exception number.
+    unsafe { asm!("mrs {}, ipsr", out(reg) ipsr, options(nomem, nostack, preserves_flags)) };
+    // Safety: the fault handler's assembly found the exception frame. The
+    // status registers are always readable.
+    let record = unsafe {
+        FaultRecord {
+            vector: ipsr & 0x1FF,
+            cfsr: SCB_CFSR.read_volatile(),
+            hfsr: SCB_HFSR.read_volatile(),
+            mmfar: SCB_MMFAR.read_volatile(),
+            bfar: SCB_BFAR.read_volatile(),
+            frame: frame.read_volatile(),
+        }
+    };
+
+    if let Some(stored) = stored_fault() {
+        // Safety: the record is reserved for the fault handler. The magic number is
+        // written last, then the record is cleaned from the data cache, so that it
+        // survives a reset.
+        unsafe {
+            (&raw mut (*stored).record).write_volatile(record);
+            asm!("dmb", options(nostack, preserves_flags));
+            (&raw mut (*stored).magic).write_volatile(FAULT_RECORD_MAGIC);
+            asm!("dsb", options(nostack, preserves_flags));
+            let start = stored as usize & !31;
+            let end = stored as usize + size_of::<StoredFault>();
+            for line in (start..end).step_by(32) {
+                SCB_DCCMVAC.write_volatile(line as u32);
+            }
+            asm!("dsb", "isb", options(nostack, preserves_flags));
+        }
+    }
+
+    let hook: usize;
+    // Safety: loads a constant from the linker script.
+    unsafe { asm!("ldr {}, =__imxrt_rt_fault_hook", out(reg) hook, options(nomem, nostack)) };
+    if hook != 0 {
+        // Safety: the host only sets the hook to the user's FaultRecorded function.
+        let hook: extern "C" fn(&FaultRecord) -> ! = unsafe { core::mem::transmute(hook) };
+        hook(&record);
+    }
+
+    // Safety: requests a system reset.
+    unsafe {
+        SCB_AIRCR.write_volatile(0x05FA_0004);
+        asm!("dsb", options(nostack, preserves_flags));
+    }
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+/// Returns the record of the most recent fault.
+///
+/// Returns `None` if the runtime doesn't have a fault handler, or if there's no
+/// valid record. The record remains until you [clear](clear_fault_record) it, or
+/// until the next fault.
+///
+/// ```no_run
+/// if let Some(record) = imxrt_rt::fault_record() {
+///     let pc = record.frame.pc();
+///     // ...
+///     imxrt_rt::clear_fault_record();
+/// }
+/// ```
+pub fn fault_record() -> Option<FaultRecord> {
+    let stored = stored_fault()?;
+    // Safety: the record is reserved for the fault handler. The record is
+    // only read if the magic number marks it as valid.
+    unsafe {
+        ((&raw const (*stored).magic).read_volatile() == FAULT_RECORD_MAGIC)
+            .then(|| (&raw const (*stored).record).read_volatile())
+    }
+}
+
+/// Invalidate the fault record.
+///
+/// Does nothing if the runtime doesn't have a fault handler.
+pub fn clear_fault_record() {
+    if let Some(stored) = stored_fault() {
+        // Safety: the record is reserved for the fault handler.
+        unsafe { (&raw mut (*stored).magic).write_volatile(0) };
+    }
+}
+
+/// Why a program can't be booted.
+///
+/// Returned by [`BootImage::new`] and [`BootImage::new_position_independent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BootImageError {
+    /// The partition doesn't start with a 1024-byte aligned vector table.
+    VectorTable,
+    /// The initial stack pointer isn't 8-byte aligned, or it's not in RAM.
+    StackPointer,
+    /// The reset vector isn't a Thumb function in the partition.
+    ResetVector,
+}
+
+/// Returns the ranges of RAM that may hold an initial stack pointer.
+///
+/// Each range spans the most TCM and OCRAM that the family can have.
+/// A stack pointer may equal the end of its range, but not its start.
+fn ram_ranges() -> [Range<u32>; 3] {
+    let family: u32;
+    // Safety: loads a constant from the linker script.
+    unsafe { asm!("ldr {}, =__imxrt_rt_v0.2", out(reg) family, options(nomem, nostack)) };
+    match family {
+        0x1180 => [
+            0x0FFC_0000..0x1000_0000,
+            0x2000_0000..0x2004_0000,
+            0x2048_0000..0x2054_0000,
+        ],
+        0x1160 | 0x1170 => [
+            0x0000_0000..0x0008_0000,
+            0x2000_0000..0x2008_0000,
+            0x2024_0000..0x2040_0000,
+        ],
+        _ => [
+            0x0000_0000..0x0008_0000,
+            0x2000_0000..0x2008_0000,
+            0x2020_0000..0x2030_0000,
+        ],
+    }
+}
+
+/// A program that's ready to boot.
+///
+/// Bootloaders use a `BootImage` to launch programs built with `RuntimeBuilder::in_flash`.
+/// The program's vector table is at the start of its flash partition.
+///
+/// ```no_run
+/// use imxrt_rt::BootImage;
+///
+/// const PARTITION: core::ops::Range<usize> = 0x6010_0000..0x6020_0000;
+/// let partition = PARTITION.start as *const u32..PARTITION.end as *const u32;
+/// // Safety: the partition is in memory-mapped flash.
+/// if let Ok(image) = unsafe { BootImage::new(partition) } {
+///     // Safety: we're done with this program.
+///     unsafe { image.boot() };
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BootImage {
+    vector_table: *const u32,
+    stack_pointer: u32,
+    reset_vector: u32,
+}
+
+impl BootImage {
+    /// Check the program in `partition`.
+    ///
+    /// The check makes sure that the vector table is 1024-byte aligned, the initial
+    /// stack pointer is in RAM, and the reset vector is inside the partition. It
+    /// doesn't check the rest of the program.
+    ///
+    /// # Safety
+    ///
+    /// The first two words of the partition must be readable.
+    pub unsafe fn new(partition: Range<*const u32>) -> Result<Self, BootImageError> {
+        let link_address = partition.start;
+        // Safety: see the caller's contract.
+        unsafe { Self::new_position_independent(partition, link_address) }
+    }
+
+    /// Check the position-independent program in `partition`.
+    ///
+    /// `link_address` is the flash address that the program was linked for. The
+    /// program's reset vector is moved by the distance between the partition and
+    /// that address, so the same program can boot from either of two slots. Otherwise,
+    /// this is the same as [`new`](Self::new). See `RuntimeBuilder::position_independent`
+    /// to build these programs.
+    ///
+    /// # Safety
+    ///
+    /// The first two words of the partition must be readable.
+    pub unsafe fn new_position_independent(
+        partition: Range<*const u32>,
+        link_address: *const u32,
+    ) -> Result<Self, BootImageError> {
+        let vector_table = partition.start;
+        let (start, end) = (partition.start as usize, partition.end as usize);
+        if start % 1024 != 0 || end.saturating_sub(start) < 8 {
+            return Err(BootImageError::VectorTable);
+        }
+        // Safety: the caller promises that these words are readable.
+        let (stack_pointer, reset_vector) = unsafe {
+            (
+                vector_table.read_volatile(),
+                vector_table.add(1).read_volatile(),
+            )
+        };
+        if stack_pointer % 8 != 0
+            || !ram_ranges()
+                .iter()
+                .any(|ram| ram.start < stack_pointer && stack_pointer <= ram.end)
+        {
+            return Err(BootImageError::StackPointer);
+        }
+        let reset_vector = reset_vector
+            .wrapping_add(start as u32)
+            .wrapping_sub(link_address as u32);
+        let entry = reset_vector as usize & !1;
+        if reset_vector & 1 == 0 || !(start..end).contains(&entry) {
+            return Err(BootImageError::ResetVector);
+        }
+        Ok(Self {
+            vector_table,
+            stack_pointer,
+            reset_vector,
+        })
+    }
+
+    /// Returns the program's vector table.
+    pub fn vector_table(&self) -> *const u32 {
+        self.vector_table
+    }
+
+    /// Boot the program.
+    ///
+    /// Before it branches to the reset vector, this
+    ///
+    /// - disables and clears all interrupts, disables SysTick, and clears pending
+    ///   SysTick and PendSV exceptions.
+    /// - cleans, invalidates, and disables the data and instruction caches.
+    /// - disables the MPU.
+    /// - points VTOR at the program's vector table.
+    /// - switches to the main stack, and sets the program's initial stack pointer.
+    ///
+    /// Interrupts are unmasked, like they are after reset. Since every interrupt is
+    /// disabled, and no system exception is pending, none can fire until the program
+    /// enables it.
+    ///
+    /// # Safety
+    ///
+    /// Call this from privileged thread mode. Nothing in the calling program runs
+    /// after the branch. Memory and peripherals stay as they are, except for the
+    /// state listed above.
+    pub unsafe fn boot(self) -> ! {
+        const SYST_CSR: *mut u32 = core::ptr::without_provenance_mut(0xE000_E010);
+        const NVIC_ICER: *mut u32 = core::ptr::without_provenance_mut(0xE000_E180);
+        const NVIC_ICPR: *mut u32 = core::ptr::without_provenance_mut(0xE000_E280);
+        const SCB_ICSR: *mut u32 = core::ptr::without_provenance_mut(0xE000_ED04);
+        const SCB_VTOR: *mut u32 = core::ptr::without_provenance_mut(0xE000_ED08);
+        const SCB_CCR: *mut u32 = core::ptr::without_provenance_mut(0xE000_ED14);
+        const SCB_CCSIDR: *const u32 = core::ptr::without_provenance(0xE000_ED80);
+        const SCB_CSSELR: *mut u32 = core::ptr::without_provenance_mut(0xE000_ED84);
+        const MPU_CTRL: *mut u32 = core::ptr::without_provenance_mut(0xE000_ED94);
+        const SCB_ICIALLU: *mut u32 = core::ptr::without_provenance_mut(0xE000_EF50);
+        const SCB_DCCISW: *mut u32 = core::ptr::without_provenance_mut(0xE000_EF74);
+        const ICSR_PENDSTCLR: u32 = 1 << 25;
+        const ICSR_PENDSVCLR: u32 = 1 << 27;
+        const CCR_DC: u32 = 1 << 16;
+        const CCR_IC: u32 = 1 << 17;
+
+        // Safety: the caller promises privileged thread mode, and that nothing else
+        // needs this core's state. The register accesses follow the Cortex-M7 manual.
+        unsafe {
+            asm!("cpsid i", options(nomem, nostack, preserves_flags));
+            SYST_CSR.write_volatile(0);
+            for register in 0..8 {
+                NVIC_ICER.add(register).write_volatile(!0);
+                NVIC_ICPR.add(register).write_volatile(!0);
+            }
+            // A pending system exception would run the program's handler before its reset.
+            SCB_ICSR.write_volatile(ICSR_PENDSTCLR | ICSR_PENDSVCLR);
+            asm!("dsb", "isb", options(nostack, preserves_flags));
+
+            // Disable the data cache, then clean and invalidate every line.
+            SCB_CSSELR.write_volatile(0);
+            asm!("dsb", options(nostack, preserves_flags));
+            SCB_CCR.write_volatile(SCB_CCR.read_volatile() & !CCR_DC);
+            asm!("dsb", options(nostack, preserves_flags));
+            let ccsidr = SCB_CCSIDR.read_volatile();
+            let sets = (ccsidr >> 13) & 0x7FFF;
+            let ways = (ccsidr >> 3) & 0x3FF;
+            for set in 0..=sets {
+                for way in 0..=ways {
+                    SCB_DCCISW.write_volatile((set << 5) | (way << 30));
+                }
+            }
+            asm!("dsb", "isb", options(nostack, preserves_flags));
+
+            SCB_CCR.write_volatile(SCB_CCR.read_volatile() & !CCR_IC);
+            asm!("dsb", "isb", options(nostack, preserves_flags));
+            SCB_ICIALLU.write_volatile(0);
+            MPU_CTRL.write_volatile(0);
+            SCB_VTOR.write_volatile(self.vector_table as u32);
+            asm!("dsb", "isb", options(nostack, preserves_flags));
+
+            asm!(
+                "msr control, {zero}",
+                "isb",
+                "msr msp, {sp}",
+                "cpsie i",
+                "bx {reset}",
+                zero = in(reg) 0,
+                sp = in(reg) self.stack_pointer,
+                reset = in(reg) self.reset_vector,
+                options(noreturn),
+            );
+        }
+    }
+}
+
+/// Returns the start and end of a heap allocated with `RuntimeBuilder::named_heap`.
+///
+/// Supply the heap's name as an identifier. The macro evaluates to a
+/// `Range<*mut u32>`. Both pointers are guaranteed to be 4-byte aligned.
+///
+/// ```no_run
+/// let dsp = imxrt_rt::named_heap!(dsp);
+/// let dsp_bytes = dsp.end as usize - dsp.start as usize;
+/// ```
+///
+/// If the runtime doesn't define a heap with that name, linking fails.
+#[macro_export]
+macro_rules! named_heap {
+    ($name:ident) => {{
+        unsafe extern "C" {
+            #[link_name = concat!("__sheap_", stringify!($name))]
+            static mut START: ::core::ffi::c_void;
+            #[link_name = concat!("__eheap_", stringify!($name))]
+            static mut END: ::core::ffi::c_void;
+        }
+        (&raw mut START as *mut u32)..(&raw mut END as *mut u32)
+    }};
+}
@@ -187,6 +187,21 @@ impl<'a> ImxrtBinary<'a> {
             .ok_or_else(|| format!("Could not find {section_name} in program").into())
     }
 
+    /// Returns the records in the copy table.
+    ///
+    /// Each record is a section's start address, end address, and load address.
+    fn copy_table(&self) -> Vec<[u32; 3]> {
+        let sec = self
+            .section_header(".copy_table")
+            .expect("Section .copy_table not found");
+        (0..sec.sh_size as usize / 12)
+            .map(|record| {
+                let offset = sec.sh_offset as usize + record * 12;
+                [0, 4, 8].map(|word| self.read_u32(offset + word))
+            })
+            .collect()
+    }
+
     fn section_lma(&self, section_name: &str) -> u64 {
         let sec = self
             .section_header(section_name)
@@ -242,6 +257,27 @@ const fn aligned(value: u64, alignment: u64) -> u64 {
     (value + (alignment - 1)) & !(alignment - 1)
 }
 
+/// Assert that the copy table holds a record for each relocated section, in order.
+fn assert_copy_table(binary: &ImxrtBinary, sections: &[&str]) {
+    let records = binary.copy_table();
+    assert_eq!(records.len(), sections.len(), "copy table records");
+    for (record, name) in records.iter().zip(sections) {
+        let section = binary.section(name).unwrap();
+        assert_eq!(
+            [u64::from(record[0]), u64::from(record[1])],
+            [section.address, section.address + section.size],
+            "{name} copy table record"
+        );
+        if section.size > 0 {
+            assert_eq!(
+                u64::from(record[2]),
+                binary.section_lma(name),
+                "{name} copy table record"
+            );
+        }
+    }
+}
+
 const IMXRT1010_INTERRUPTS: u64 = 80;
 const IMXRT1060_INTERRUPTS: u64 = 158;
 const IMXRT1170_INTERRUPTS: u64 = 218;
@@ -309,18 +345,20 @@ fn imxrt1010evk() {
     assert_eq!(binary.section_lma(".vector_table"), 0x6000_2000);
 
     let xip = binary.section(".xip").unwrap();
+    let copy_table = binary.section(".copy_table").unwrap();
+    assert_copy_table(&binary, &[".itcm", ".dtcm", ".ocram"]);
     let text = binary.section(".text").unwrap();
     assert_eq!(text.address, ITCM, "text");
     assert_eq!(
         binary.section_lma(".text"),
-        aligned(0x6000_2000 + vector_table.size + xip.size, 4),
-        "text VMA expected behind vector table"
+        aligned(0x6000_2000 + vector_table.size + xip.size, 4) + copy_table.size,
+        "text VMA expected behind the copy table"
     );
 
     let rodata = binary.section(".rodata").unwrap();
     assert_eq!(
         rodata.address,
-        aligned(0x6000_2000 + vector_table.size + text.size + xip.size, 4),
+        aligned(0x6000_2000 + vector_table.size + xip.size, 4) + copy_table.size + text.size,
         "rodata LMA & VMA expected behind text"
     );
     assert_eq!(rodata.address, binary.section_lma(".rodata"));
@@ -377,6 +415,56 @@ fn imxrt1010evk() {
     assert!(binary.symbol("Reset").is_some());
 }
 
+#[cfg(feature = "report")]
+#[test]
+#[ignore = "building an example can take time"]
+fn imxrt1010evk_memory_report() {
+    use imxrt_rt::{Family, FlexRamKind, Memory, MemoryReport};
+
+    let path = cargo_build("imxrt1010evk").expect("Unable to build example");
+    let contents = fs::read(path).expect("Could not read ELF file");
+    let report = MemoryReport::from_elf(&contents).expect("Could not produce report");
+    assert_eq!(report.family, Family::Imxrt1010);
+
+    let memories: Vec<Memory> = report.regions.iter().map(|r| r.memory).collect();
+    assert_eq!(
+        memories,
+        [Memory::Flash, Memory::Itcm, Memory::Dtcm, Memory::Ocram]
+    );
+    for region in &report.regions {
+        assert_eq!(
+            region.used + region.padding + region.free,
+            region.length,
+            "{:?}",
+            region.memory
+        );
+    }
+
+    let dtcm = &report.regions[2];
+    let names: Vec<&str> = dtcm.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, [".stack", ".vector_table", ".heap"]);
+    assert_eq!(dtcm.origin as u64, DTCM);
+
+    let flash = &report.regions[0];
+    let vector_table = flash
+        .sections
+        .iter()
+        .find(|s| s.name == ".vector_table")
+        .unwrap();
+    assert!(vector_table.loaded);
+    assert_eq!(vector_table.address, 0x6000_2000);
+
+    let kinds: Vec<FlexRamKind> = report.flexram.iter().map(|f| f.kind).collect();
+    assert_eq!(
+        kinds,
+        [FlexRamKind::Itcm, FlexRamKind::Dtcm, FlexRamKind::Ocram]
+    );
+    assert_eq!(report.flexram[1].used_banks, Some(1));
+    assert_eq!(report.flexram[2].used_banks, None);
+
+    assert!(report.to_json().starts_with("{\"family\":\"Imxrt1010\""));
+}
+
 #[test]
 #[ignore = "building an example can take time"]
 fn imxrt1010evk_ram() {
@@ -424,11 +512,16 @@ fn imxrt1010evk_ram() {
     );
 
     let xip = binary.section(".xip").unwrap();
+    let copy_table = binary.section(".copy_table").unwrap();
     let text = binary.section(".text").unwrap();
-    assert_eq!(text.address, aligned(ITCM + xip.size, 4), "text");
+    assert_eq!(
+        text.address,
+        aligned(ITCM + xip.size, 4) + copy_table.size,
+        "text"
+    );
     assert_eq!(
         binary.section_lma(".text"),
-        aligned(ITCM + xip.size, 4),
+        aligned(ITCM + xip.size, 4) + copy_table.size,
         "text LMA == VMA for RAM boot"
     );
 
@@ -551,16 +644,17 @@ fn baseline_teensy4(
     );
     assert_eq!(binary.section_lma(".vector_table"), 0x6000_2000);
 
+    let copy_table = binary.section(".copy_table").unwrap();
     let text = binary.section(".text").unwrap();
     let expected_text_address = aligned(
         binary.section_lma(".vector_table") + vector_table.size + xip.size,
         4,
-    );
+    ) + copy_table.size;
     assert_eq!(text.address, expected_text_address, "text");
     assert_eq!(
         binary.section_lma(".text"),
-        aligned(0x6000_2000 + vector_table.size + xip.size, 4),
-        "text VMA expected behind vector table"
+        aligned(0x6000_2000 + vector_table.size + xip.size, 4) + copy_table.size,
+        "text VMA expected behind the copy table"
     );
 
     let rodata = binary.section(".rodata").unwrap();
@@ -797,12 +891,13 @@ fn imxrt1170evk_cm7() {
     assert_eq!(binary.section_lma(".vector_table"), 0x3000_2000);
 
     let xip = binary.section(".xip").unwrap();
+    let copy_table = binary.section(".copy_table").unwrap();
     let text = binary.section(".text").unwrap();
     assert_eq!(text.address, ITCM, "text");
     assert_eq!(
         binary.section_lma(".text"),
-        aligned(0x3000_2000 + vector_table.size + xip.size, 4),
-        "text VMA expected behind vector table"
+        aligned(0x3000_2000 + vector_table.size + xip.size, 4) + copy_table.size,
+        "text VMA expected behind the copy table"
     );
 
     let rodata = binary.section(".rodata").unwrap();
@@ -939,12 +1034,13 @@ fn imxrt1170evk_cm7_nonboot() {
         "text VMA expected behind vector table"
     );
 
+    let copy_table = binary.section(".copy_table").unwrap();
     let text = binary.section(".text").unwrap();
     assert_eq!(text.address, ITCM, "text");
     assert_eq!(
         binary.section_lma(".text"),
-        0x3000_0000 + IMAGE_OFFSET + aligned(xip.size, 4) + vector_table.size,
-        "text VMA expected behind vector table"
+        0x3000_0000 + IMAGE_OFFSET + aligned(xip.size, 4) + vector_table.size + copy_table.size,
+        "text VMA expected behind the copy table"
     );
 
     let rodata = binary.section(".rodata").unwrap();
@@ -1071,12 +1167,13 @@ fn imxrt1170evk_cm7_secondary() {
     );
 
     let xip = binary.section(".xip").unwrap();
+    let copy_table = binary.section(".copy_table").unwrap();
     let text = binary.section(".text").unwrap();
     assert_eq!(text.address, ITCM, "text");
     assert_eq!(
         binary.section_lma(".text"),
-        aligned(0x3000_2000 + IMAGE_OFFSET + vector_table.size + xip.size, 4),
-        "text VMA expected behind vector table"
+        aligned(0x3000_2000 + IMAGE_OFFSET + vector_table.size + xip.size, 4) + copy_table.size,
+        "text VMA expected behind the copy table"
     );
 
     let rodata = binary.section(".rodata").unwrap();
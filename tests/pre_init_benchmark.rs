@@ -0,0 +1,414 @@
+//! Count the instructions executed by the pre-init copy and zero routines.
+//!
+//! The tests build an example, then interpret `__imxrt_rt_copy` and
+//! `__imxrt_rt_zero` for representative section sizes. Each count is compared
+//! with the loops that the routines replaced. The old copy loop moved one word
+//! per iteration, and the old zero loop stored one double word per iteration.
+//!
+//! The interpreter only understands the handful of Thumb-2 instructions used by
+//! these routines. If a routine changes, the interpreter may need to learn a new
+//! instruction.
+
+use goblin::elf::Elf;
+use std::{collections::VecDeque, fs, path::PathBuf, process::Command};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Build an example, returning a path to the ELF.
+fn cargo_build(board: &str) -> Result<PathBuf> {
+    let status = Command::new("cargo")
+        .arg("build")
+        .arg("--example=blink-rtic")
+        .arg(format!("--features=board/{board},board/rtic"))
+        .arg("--target=thumbv7em-none-eabihf")
+        .arg(format!("--target-dir=target/{board}"))
+        .arg("--quiet")
+        .spawn()?
+        .wait()?;
+
+    if !status.success() {
+        return Err(
+            format!("Building board '{board}' failed: process returned {status:?}",).into(),
+        );
+    }
+
+    let path = PathBuf::from(format!(
+        "target/{board}/thumbv7em-none-eabihf/debug/examples/blink-rtic"
+    ));
+    Ok(path)
+}
+
+/// The instructions of a function in the program.
+struct Routine {
+    address: u32,
+    code: Vec<u8>,
+}
+
+impl Routine {
+    fn new(elf: &Elf, contents: &[u8], symbol_name: &str) -> Result<Self> {
+        let sym = elf
+            .syms
+            .iter()
+            .find(|sym| elf.strtab.get_at(sym.st_name) == Some(symbol_name))
+            .ok_or_else(|| format!("Could not find {symbol_name} in program"))?;
+        let address = sym.st_value & !1;
+        let sec = elf
+            .section_headers
+            .iter()
+            .find(|sec| {
+                sec.sh_addr <= address && address + sym.st_size <= sec.sh_addr + sec.sh_size
+            })
+            .ok_or_else(|| format!("Could not find the section holding {symbol_name}"))?;
+        let offset = (sec.sh_offset + address - sec.sh_addr) as usize;
+        Ok(Self {
+            address: address as u32,
+            code: contents[offset..offset + sym.st_size as usize].to_vec(),
+        })
+    }
+
+    fn halfword(&self, pc: u32) -> u16 {
+        let offset = (pc - self.address) as usize;
+        u16::from_le_bytes([self.code[offset], self.code[offset + 1]])
+    }
+}
+
+/// A window of RAM, starting at some address.
+struct Ram {
+    address: u32,
+    bytes: Vec<u8>,
+}
+
+impl Ram {
+    fn offset(&self, address: u32) -> usize {
+        (address - self.address) as usize
+    }
+    fn load(&self, address: u32, size: usize) -> u32 {
+        let offset = self.offset(address);
+        let mut word = [0; 4];
+        word[..size].copy_from_slice(&self.bytes[offset..offset + size]);
+        u32::from_le_bytes(word)
+    }
+    fn store(&mut self, address: u32, size: usize, value: u32) {
+        let offset = self.offset(address);
+        self.bytes[offset..offset + size].copy_from_slice(&value.to_le_bytes()[..size]);
+    }
+}
+
+/// The registers in a LDM / STM register list, lowest first.
+fn register_list(list: u16) -> impl Iterator<Item = usize> {
+    (0..16).filter(move |reg| list & (1 << reg) != 0)
+}
+
+const LR: usize = 14;
+const ALWAYS: u16 = 0b1110;
+
+#[derive(Default)]
+struct Cpu {
+    regs: [u32; 16],
+    n: bool,
+    z: bool,
+    c: bool,
+    v: bool,
+    /// Conditions of the remaining instructions in an IT block.
+    it: VecDeque<u16>,
+}
+
+impl Cpu {
+    fn condition_passed(&self, cond: u16) -> bool {
+        let result = match cond >> 1 {
+            0 => self.z,
+            1 => self.c,
+            2 => self.n,
+            3 => self.v,
+            4 => self.c && !self.z,
+            5 => self.n == self.v,
+            6 => !self.z && self.n == self.v,
+            _ => return true,
+        };
+        result ^ (cond & 1 == 1)
+    }
+
+    fn subtract(&mut self, a: u32, b: u32) -> u32 {
+        let result = a.wrapping_sub(b);
+        self.n = result >> 31 == 1;
+        self.z = result == 0;
+        self.c = a >= b;
+        self.v = ((a ^ b) & (a ^ result)) >> 31 == 1;
+        result
+    }
+
+    fn load_multiple(&mut self, ram: &Ram, rn: usize, list: u16) {
+        for reg in register_list(list) {
+            self.regs[reg] = ram.load(self.regs[rn], 4);
+            self.regs[rn] += 4;
+        }
+    }
+
+    fn store_multiple(&mut self, ram: &mut Ram, rn: usize, list: u16) {
+        for reg in register_list(list) {
+            ram.store(self.regs[rn], 4, self.regs[reg]);
+            self.regs[rn] += 4;
+        }
+    }
+
+    /// Run the routine until it returns, producing the number of executed instructions.
+    ///
+    /// Instructions skipped by an IT block are counted, since they still issue.
+    fn call(&mut self, routine: &Routine, ram: &mut Ram) -> usize {
+        const RETURN: u32 = 0xFFFF_FFFF;
+        self.regs[LR] = RETURN;
+        let mut pc = routine.address;
+        let mut count = 0;
+        while pc != RETURN {
+            count += 1;
+            let cond = self.it.pop_front().unwrap_or(ALWAYS);
+            let hw1 = routine.halfword(pc);
+            if matches!(hw1 >> 11, 0b11101..=0b11111) {
+                let hw2 = routine.halfword(pc + 2);
+                pc += 4;
+                if self.condition_passed(cond) {
+                    self.execute32(ram, hw1, hw2);
+                }
+            } else {
+                pc += 2;
+                if self.condition_passed(cond) {
+                    pc = self.execute16(ram, hw1, pc);
+                }
+            }
+        }
+        count
+    }
+
+    /// Execute a 16-bit instruction, producing the next PC.
+    fn execute16(&mut self, ram: &mut Ram, op: u16, pc: u32) -> u32 {
+        let low = |shift: u16| usize::from((op >> shift) & 0b111);
+        match op {
+            // MOVS Rd, #imm8
+            _ if op >> 11 == 0b00100 => {
+                let rd = low(8);
+                self.regs[rd] = u32::from(op & 0xFF);
+                self.n = false;
+                self.z = self.regs[rd] == 0;
+            }
+            // LSLS Rd, Rm, #imm5
+            _ if op >> 11 == 0b00000 && (op >> 6) & 0x1F != 0 => {
+                let shift = u32::from((op >> 6) & 0x1F);
+                let rm = self.regs[low(3)];
+                self.c = (rm >> (32 - shift)) & 1 == 1;
+                let result = rm << shift;
+                self.n = result >> 31 == 1;
+                self.z = result == 0;
+                self.regs[low(0)] = result;
+            }
+            // SUBS Rd, Rn, Rm
+            _ if op >> 9 == 0b0001101 => {
+                self.regs[low(0)] = self.subtract(self.regs[low(3)], self.regs[low(6)]);
+            }
+            // SUBS Rdn, #imm8
+            _ if op >> 11 == 0b00111 => {
+                let rdn = low(8);
+                self.regs[rdn] = self.subtract(self.regs[rdn], u32::from(op & 0xFF));
+            }
+            // CMP Rn, Rm
+            _ if op >> 6 == 0b0100001010 => {
+                self.subtract(self.regs[low(0)], self.regs[low(3)]);
+            }
+            // B<c> label
+            _ if op >> 12 == 0b1101 && (op >> 9) & 0b111 != 0b111 => {
+                if self.condition_passed((op >> 8) & 0xF) {
+                    let offset = i32::from((op & 0xFF) as i8) << 1;
+                    return pc.wrapping_add(2).wrapping_add_signed(offset);
+                }
+            }
+            // IT
+            _ if op >> 8 == 0xBF && op & 0xF != 0 => {
+                let (firstcond, mask) = ((op >> 4) & 0xF, op & 0xF);
+                self.it.push_back(firstcond);
+                for k in 1..4 - mask.trailing_zeros() as u16 {
+                    self.it
+                        .push_back((firstcond & 0b1110) | ((mask >> (4 - k)) & 1));
+                }
+            }
+            // BX LR
+            0x4770 => return self.regs[LR],
+            // LDM Rn!, {...}
+            _ if op >> 11 == 0b11001 => self.load_multiple(ram, low(8), op & 0xFF),
+            // STM Rn!, {...}
+            _ if op >> 11 == 0b11000 => self.store_multiple(ram, low(8), op & 0xFF),
+            // LDRB Rt, [Rn, #imm5]
+            _ if op >> 11 == 0b01111 => {
+                let address = self.regs[low(3)] + u32::from((op >> 6) & 0x1F);
+                self.regs[low(0)] = ram.load(address, 1);
+            }
+            // STRB Rt, [Rn, #imm5]
+            _ if op >> 11 == 0b01110 => {
+                let address = self.regs[low(3)] + u32::from((op >> 6) & 0x1F);
+                ram.store(address, 1, self.regs[low(0)]);
+            }
+            _ => panic!("Unknown 16-bit instruction {op:#06x}"),
+        }
+        pc
+    }
+
+    /// Execute a 32-bit instruction. None of these branch.
+    fn execute32(&mut self, ram: &mut Ram, hw1: u16, hw2: u16) {
+        let rn = usize::from(hw1 & 0xF);
+        let rt = usize::from(hw2 >> 12);
+        match hw1 & 0xFFF0 {
+            // LDM.W Rn!, {...}
+            0xE8B0 => self.load_multiple(ram, rn, hw2),
+            // STM.W Rn!, {...}
+            0xE8A0 => self.store_multiple(ram, rn, hw2),
+            // STRD Rt, Rt2, [Rn], #imm8
+            0xE8E0 => {
+                let rt2 = usize::from((hw2 >> 8) & 0xF);
+                ram.store(self.regs[rn], 4, self.regs[rt]);
+                ram.store(self.regs[rn] + 4, 4, self.regs[rt2]);
+                self.regs[rn] += u32::from(hw2 & 0xFF) << 2;
+            }
+            // LDR{B,H} / STR{B,H} Rt, [Rn], #imm8
+            _ if hw1 & 0xFF80 == 0xF800 && hw2 & 0xF00 == 0xB00 => {
+                let size = 1 << ((hw1 >> 5) & 0b11);
+                if hw1 & (1 << 4) != 0 {
+                    self.regs[rt] = ram.load(self.regs[rn], size);
+                } else {
+                    ram.store(self.regs[rn], size, self.regs[rt]);
+                }
+                self.regs[rn] += u32::from(hw2 & 0xFF);
+            }
+            _ => panic!("Unknown 32-bit instruction {hw1:#06x} {hw2:#06x}"),
+        }
+    }
+}
+
+/// The copy and zero routines of a program.
+struct PreInit {
+    copy: Routine,
+    zero: Routine,
+}
+
+/// Where the RAM window starts. Destinations are at the start of the window.
+const RAM_ADDRESS: u32 = 0x2000_0000;
+
+impl PreInit {
+    fn new(path: &PathBuf) -> Result<Self> {
+        let contents = fs::read(path)?;
+        let elf = Elf::parse(&contents)?;
+        Ok(Self {
+            copy: Routine::new(&elf, &contents, "__imxrt_rt_copy")?,
+            zero: Routine::new(&elf, &contents, "__imxrt_rt_zero")?,
+        })
+    }
+
+    /// Copy `size` bytes, returning the instruction count.
+    ///
+    /// The count includes the call into the routine.
+    fn copy(&self, size: usize) -> usize {
+        let source: Vec<u8> = (0..size).map(|idx| idx as u8 ^ 0xA5).collect();
+        // Destination, then a guard word, then the source.
+        let src = RAM_ADDRESS + (size as u32 + 4).next_multiple_of(4);
+        let mut ram = Ram {
+            address: RAM_ADDRESS,
+            bytes: vec![0xEE; (src - RAM_ADDRESS) as usize + size],
+        };
+        ram.bytes[(src - RAM_ADDRESS) as usize..].copy_from_slice(&source);
+
+        let mut cpu = Cpu::default();
+        cpu.regs[0] = RAM_ADDRESS;
+        cpu.regs[1] = RAM_ADDRESS + size as u32;
+        cpu.regs[2] = src;
+        let count = 1 + cpu.call(&self.copy, &mut ram);
+
+        assert_eq!(&ram.bytes[..size], &source[..], "Copying {size} bytes");
+        assert!(
+            ram.bytes[size..(src - RAM_ADDRESS) as usize]
+                .iter()
+                .all(|&byte| byte == 0xEE),
+            "Copying {size} bytes overran the destination"
+        );
+        count
+    }
+
+    /// Zero `size` bytes, returning the instruction count.
+    ///
+    /// The count includes the call into the routine.
+    fn zero(&self, size: usize) -> usize {
+        let mut ram = Ram {
+            address: RAM_ADDRESS,
+            bytes: vec![0xEE; size + 8],
+        };
+
+        let mut cpu = Cpu::default();
+        cpu.regs[0] = RAM_ADDRESS;
+        cpu.regs[1] = RAM_ADDRESS + size as u32;
+        let count = 1 + cpu.call(&self.zero, &mut ram);
+
+        assert!(
+            ram.bytes[..size].iter().all(|&byte| byte == 0),
+            "Zeroing {size} bytes"
+        );
+        assert!(
+            ram.bytes[size..].iter().all(|&byte| byte == 0xEE),
+            "Zeroing {size} bytes overran the region"
+        );
+        count
+    }
+}
+
+/// Instructions executed by the word-at-a-time copy loop, excluding address loads.
+fn word_copy_instructions(size: usize) -> usize {
+    // cmp / beq to skip the copy, five instructions per word, then cmp / beq to exit.
+    2 + 5 * size / 4 + 2
+}
+
+/// Instructions executed by the double-word-at-a-time zero loop, excluding address loads.
+fn double_word_zero_instructions(size: usize) -> usize {
+    // movw, four instructions per double word, then cmp / bge to exit.
+    1 + 4 * size / 8 + 2
+}
+
+/// Section sizes, from a small vector table up to a large OCRAM image.
+const SIZES: &[usize] = &[256, 1024, 4 * 1024, 64 * 1024, 256 * 1024];
+
+#[test]
+#[ignore = "building an example can take time"]
+fn teensy4_pre_init_benchmark() {
+    let path = cargo_build("teensy4").expect("Unable to build example");
+    let pre_init = PreInit::new(&path).unwrap();
+
+    println!(
+        "{:>8} {:>12} {:>12} {:>12} {:>12}",
+        "bytes", "old copy", "copy", "old zero", "zero"
+    );
+    for &size in SIZES {
+        let (copy, zero) = (pre_init.copy(size), pre_init.zero(size));
+        let (old_copy, old_zero) = (
+            word_copy_instructions(size),
+            double_word_zero_instructions(size),
+        );
+        println!("{size:>8} {old_copy:>12} {copy:>12} {old_zero:>12} {zero:>12}");
+
+        assert!(
+            2 * copy < old_copy,
+            "Copying {size} bytes takes {copy} instructions"
+        );
+        assert!(
+            2 * zero < old_zero,
+            "Zeroing {size} bytes takes {zero} instructions"
+        );
+    }
+}
+
+#[test]
+#[ignore = "building an example can take time"]
+fn teensy4_pre_init_tails() {
+    let path = cargo_build("teensy4").expect("Unable to build example");
+    let pre_init = PreInit::new(&path).unwrap();
+
+    for size in 0..=96 {
+        pre_init.copy(size);
+    }
+    for size in (0..=96).step_by(4) {
+        pre_init.zero(size);
+    }
+}